@@ -0,0 +1,388 @@
+//! Fanning received frames out to multiple consumers
+//!
+//! A `Dispatcher` owns a `Receiver`, and delivers every received frame to the `Channel` of each
+//! subscriber whose `SoftwareFilter` accepts it. Each subscriber then receives its frames from
+//! its own `Channel`, typically in a task of its own.
+//!
+//! Without the `critical-section` feature the channels can only be shared by tasks of a single
+//! executor. With it, they can also be used from other executors and interrupt handlers.
+
+use core::future::poll_fn;
+use core::task::{Poll, Waker};
+
+use crate::filter::SoftwareFilter;
+use crate::mutex::Mutex;
+use crate::{Filter, Receiver};
+
+/// What to do with a frame for a subscriber whose `Channel` is full.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Overflow {
+    /// Discard the oldest frame in the channel to make room for the new one.
+    DropOldest,
+
+    /// Discard the new frame.
+    DropNewest,
+
+    /// Wait until the subscriber made room, delaying the delivery to all other subscribers.
+    Backpressure,
+}
+
+struct ChannelState<F, const DEPTH: usize> {
+    frames: [Option<F>; DEPTH],
+    head: usize,
+    len: usize,
+    dropped: usize,
+    receiver: Option<Waker>,
+    sender: Option<Waker>,
+}
+
+impl<F, const DEPTH: usize> ChannelState<F, DEPTH> {
+    fn push(&mut self, frame: F) {
+        self.frames[(self.head + self.len) % DEPTH] = Some(frame);
+        self.len += 1;
+        if let Some(waker) = self.receiver.take() {
+            waker.wake();
+        }
+    }
+
+    fn pop(&mut self) -> Option<F> {
+        if self.len == 0 {
+            return None;
+        }
+        let frame = self.frames[self.head].take();
+        self.head = (self.head + 1) % DEPTH;
+        self.len -= 1;
+        if let Some(waker) = self.sender.take() {
+            waker.wake();
+        }
+        frame
+    }
+}
+
+/// A bounded channel holding up to `DEPTH` frames delivered by a `Dispatcher`.
+pub struct Channel<F, const DEPTH: usize> {
+    state: Mutex<ChannelState<F, DEPTH>>,
+}
+
+impl<F, const DEPTH: usize> Channel<F, DEPTH> {
+    /// Creates an empty `Channel`.
+    ///
+    /// ### Panic
+    /// Panics if `DEPTH` is 0.
+    pub fn new() -> Self {
+        assert!(DEPTH > 0, "channel depth must not be 0");
+        Channel {
+            state: Mutex::new(ChannelState {
+                frames: core::array::from_fn(|_| None),
+                head: 0,
+                len: 0,
+                dropped: 0,
+                receiver: None,
+                sender: None,
+            }),
+        }
+    }
+
+    /// Returns the oldest frame in the channel, if any.
+    pub fn try_receive(&self) -> Option<F> {
+        self.state.lock(|state| state.pop())
+    }
+
+    /// Returns the oldest frame in the channel, waiting for one if needed.
+    ///
+    /// Only one task should receive from a `Channel`.
+    pub async fn receive(&self) -> F {
+        poll_fn(|cx| {
+            self.state.lock(|state| match state.pop() {
+                Some(frame) => Poll::Ready(frame),
+                None => {
+                    state.receiver = Some(cx.waker().clone());
+                    Poll::Pending
+                }
+            })
+        })
+        .await
+    }
+
+    /// Returns the number of frames in the channel.
+    pub fn len(&self) -> usize {
+        self.state.lock(|state| state.len)
+    }
+
+    /// Returns true if the channel holds no frame.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of frames discarded because the channel was full.
+    pub fn dropped(&self) -> usize {
+        self.state.lock(|state| state.dropped)
+    }
+}
+
+impl<F, const DEPTH: usize> Default for Channel<F, DEPTH> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct Subscriber<'a, R: Receiver, const DEPTH: usize> {
+    channel: &'a Channel<R::Frame, DEPTH>,
+    filter: SoftwareFilter<R::Id>,
+    overflow: Overflow,
+}
+
+/// An error returned by `Dispatcher::subscribe` when all subscriber slots are taken.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct TooManySubscribers;
+
+/// Delivers the frames of a `Receiver` to up to `SUBSCRIBERS` channels of `DEPTH` frames.
+pub struct Dispatcher<'a, R: Receiver, const SUBSCRIBERS: usize, const DEPTH: usize> {
+    receiver: R,
+    subscribers: [Option<Subscriber<'a, R, DEPTH>>; SUBSCRIBERS],
+
+    /// The frame being delivered, and the index of the next subscriber to deliver it to.
+    pending: Option<R::Frame>,
+    next: usize,
+}
+
+impl<'a, R, const SUBSCRIBERS: usize, const DEPTH: usize> Dispatcher<'a, R, SUBSCRIBERS, DEPTH>
+where
+    R: Receiver,
+    R::Frame: Clone,
+{
+    /// Creates a `Dispatcher` without subscribers, owning `receiver`.
+    pub fn new(receiver: R) -> Self {
+        Dispatcher {
+            receiver,
+            subscribers: core::array::from_fn(|_| None),
+            pending: None,
+            next: 0,
+        }
+    }
+
+    /// Deliver the frames accepted by `filter` to `channel`, handling a full channel as
+    /// `overflow` says.
+    pub fn subscribe(
+        &mut self,
+        channel: &'a Channel<R::Frame, DEPTH>,
+        filter: SoftwareFilter<R::Id>,
+        overflow: Overflow,
+    ) -> Result<(), TooManySubscribers> {
+        let slot = self
+            .subscribers
+            .iter_mut()
+            .find(|slot| slot.is_none())
+            .ok_or(TooManySubscribers)?;
+        *slot = Some(Subscriber {
+            channel,
+            filter,
+            overflow,
+        });
+        Ok(())
+    }
+
+    /// Receive a single frame and deliver it to the subscribers accepting it.
+    ///
+    /// If the returned future is dropped while waiting for room in the `Channel` of a subscriber
+    /// applying `Overflow::Backpressure`, the frame is kept, and the next call delivers it to
+    /// the remaining subscribers instead of receiving a new one.
+    pub async fn dispatch(&mut self) -> Result<(), R::Error> {
+        let frame = match &self.pending {
+            Some(frame) => frame,
+            None => {
+                let frame = self.receiver.receive().await?;
+                self.next = 0;
+                self.pending.insert(frame)
+            }
+        };
+
+        while let Some(subscriber) = self.subscribers.get(self.next) {
+            let subscriber = match subscriber {
+                Some(subscriber) if subscriber.filter.matches(frame) => subscriber,
+                _ => {
+                    self.next += 1;
+                    continue;
+                }
+            };
+            let channel = subscriber.channel;
+
+            if subscriber.overflow == Overflow::Backpressure {
+                poll_fn(|cx| {
+                    channel.state.lock(|state| {
+                        if state.len < DEPTH {
+                            Poll::Ready(())
+                        } else {
+                            state.sender = Some(cx.waker().clone());
+                            Poll::Pending
+                        }
+                    })
+                })
+                .await;
+            }
+
+            channel.state.lock(|state| {
+                if state.len < DEPTH {
+                    state.push(frame.clone());
+                } else if subscriber.overflow == Overflow::DropOldest {
+                    state.head = (state.head + 1) % DEPTH;
+                    state.len -= 1;
+                    state.dropped += 1;
+                    state.push(frame.clone());
+                } else {
+                    state.dropped += 1;
+                }
+            });
+            self.next += 1;
+        }
+
+        self.pending = None;
+        Ok(())
+    }
+
+    /// Deliver received frames until the `Receiver` returns an error.
+    pub async fn run(&mut self) -> R::Error {
+        loop {
+            if let Err(e) = self.dispatch().await {
+                return e;
+            }
+        }
+    }
+
+    /// Returns the owned `Receiver`, e.g. to change its hardware filter.
+    pub fn receiver(&mut self) -> &mut R {
+        &mut self.receiver
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{frame, pinned, poll_once, standard, Frames};
+    use crate::{AnyId, ClassicFrame, ErrorKind};
+
+    fn filter(id: u16) -> SoftwareFilter<AnyId> {
+        SoftwareFilter::from_id(standard(id))
+    }
+
+    fn dispatcher<'a, const N: usize>(
+        frames: [ClassicFrame; N],
+    ) -> Dispatcher<'a, Frames<N>, 2, 2> {
+        Dispatcher::new(Frames::new(frames))
+    }
+
+    fn dispatch<const N: usize>(dispatcher: &mut Dispatcher<'_, Frames<N>, 2, 2>) {
+        let mut future = pinned(dispatcher.dispatch());
+        assert_eq!(poll_once(future.as_mut()), Poll::Ready(Ok(())));
+    }
+
+    #[test]
+    fn filtered_fan_out() {
+        let (one, two) = (Channel::new(), Channel::new());
+        let mut dispatcher = dispatcher([frame(1, 0), frame(2, 0), frame(3, 0)]);
+        dispatcher
+            .subscribe(&one, filter(1), Overflow::DropNewest)
+            .unwrap();
+        dispatcher
+            .subscribe(&two, SoftwareFilter::accept_all(), Overflow::DropNewest)
+            .unwrap();
+        assert_eq!(
+            dispatcher.subscribe(&two, filter(2), Overflow::DropNewest),
+            Err(TooManySubscribers)
+        );
+
+        dispatch(&mut dispatcher);
+        dispatch(&mut dispatcher);
+        assert_eq!(one.try_receive(), Some(frame(1, 0)));
+        assert_eq!(one.try_receive(), None);
+        assert_eq!(two.try_receive(), Some(frame(1, 0)));
+        assert_eq!(two.try_receive(), Some(frame(2, 0)));
+
+        dispatch(&mut dispatcher);
+        assert!(one.is_empty());
+        assert_eq!(two.len(), 1);
+    }
+
+    #[test]
+    fn overflow() {
+        let (oldest, newest) = (Channel::new(), Channel::new());
+        let mut dispatcher = dispatcher([frame(1, 0), frame(1, 1), frame(1, 2)]);
+        dispatcher
+            .subscribe(&oldest, filter(1), Overflow::DropOldest)
+            .unwrap();
+        dispatcher
+            .subscribe(&newest, filter(1), Overflow::DropNewest)
+            .unwrap();
+
+        for _ in 0..3 {
+            dispatch(&mut dispatcher);
+        }
+        assert_eq!(oldest.dropped(), 1);
+        assert_eq!(oldest.try_receive(), Some(frame(1, 1)));
+        assert_eq!(oldest.try_receive(), Some(frame(1, 2)));
+        assert_eq!(newest.dropped(), 1);
+        assert_eq!(newest.try_receive(), Some(frame(1, 0)));
+        assert_eq!(newest.try_receive(), Some(frame(1, 1)));
+    }
+
+    #[test]
+    fn backpressure() {
+        let channel = Channel::new();
+        let mut dispatcher = dispatcher([frame(1, 0), frame(1, 1), frame(1, 2)]);
+        dispatcher
+            .subscribe(&channel, filter(1), Overflow::Backpressure)
+            .unwrap();
+        dispatch(&mut dispatcher);
+        dispatch(&mut dispatcher);
+
+        let mut future = pinned(dispatcher.dispatch());
+        assert!(poll_once(future.as_mut()).is_pending());
+        assert_eq!(channel.try_receive(), Some(frame(1, 0)));
+        assert_eq!(poll_once(future.as_mut()), Poll::Ready(Ok(())));
+        drop(future);
+
+        assert_eq!(channel.dropped(), 0);
+        assert_eq!(channel.try_receive(), Some(frame(1, 1)));
+        assert_eq!(channel.try_receive(), Some(frame(1, 2)));
+    }
+
+    #[test]
+    fn dropped_dispatch_keeps_the_frame() {
+        let (waiting, other) = (Channel::new(), Channel::new());
+        let mut dispatcher = dispatcher([frame(1, 0), frame(1, 1), frame(1, 2)]);
+        dispatcher
+            .subscribe(&waiting, filter(1), Overflow::Backpressure)
+            .unwrap();
+        dispatcher
+            .subscribe(&other, filter(1), Overflow::DropOldest)
+            .unwrap();
+        dispatch(&mut dispatcher);
+        dispatch(&mut dispatcher);
+
+        let mut future = pinned(dispatcher.dispatch());
+        assert!(poll_once(future.as_mut()).is_pending());
+        drop(future);
+
+        // The next call delivers the pending frame instead of receiving a new one.
+        assert_eq!(waiting.try_receive(), Some(frame(1, 0)));
+        dispatch(&mut dispatcher);
+        assert_eq!(waiting.try_receive(), Some(frame(1, 1)));
+        assert_eq!(waiting.try_receive(), Some(frame(1, 2)));
+        assert_eq!(other.try_receive(), Some(frame(1, 1)));
+        assert_eq!(other.try_receive(), Some(frame(1, 2)));
+        assert_eq!(other.dropped(), 1);
+    }
+
+    #[test]
+    fn run_returns_the_receiver_error() {
+        let channel = Channel::<_, 2>::new();
+        let mut dispatcher = dispatcher([frame(1, 0)]);
+        dispatcher
+            .subscribe(&channel, filter(1), Overflow::DropNewest)
+            .unwrap();
+
+        let mut future = pinned(dispatcher.run());
+        assert_eq!(poll_once(future.as_mut()), Poll::Ready(ErrorKind::Other));
+        assert_eq!(channel.len(), 1);
+    }
+}
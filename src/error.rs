@@ -0,0 +1,134 @@
+//! Can errors
+
+use core::convert::Infallible;
+use core::fmt;
+
+/// A Can error
+///
+/// Implemented by the `Interface::Error` types, so generic code can tell what went wrong.
+pub trait Error: fmt::Debug {
+    /// Convert error to a generic Can error kind.
+    ///
+    /// By using this method, Can errors freely defined by HAL implementations
+    /// can be converted to a set of generic Can errors upon which generic
+    /// code can act.
+    fn kind(&self) -> ErrorKind;
+}
+
+impl Error for Infallible {
+    fn kind(&self) -> ErrorKind {
+        match *self {}
+    }
+}
+
+impl Error for ErrorKind {
+    fn kind(&self) -> ErrorKind {
+        *self
+    }
+}
+
+/// Can error kind
+///
+/// This represents a common set of Can operation errors. HAL implementations are
+/// free to define more specific or additional error types. However, by providing
+/// a mapping to these common Can errors, generic code can still react to them.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// More than 5 equal bits in a sequence have been detected.
+    Stuff,
+
+    /// A fixed-format part of a received frame has the wrong format.
+    Form,
+
+    /// The message transmitted by the node was not acknowledged by another node.
+    Acknowledge,
+
+    /// The node wanted to send a dominant bit (logical value `0`), but the monitored bus
+    /// value was recessive.
+    Bit0,
+
+    /// The node wanted to send a recessive bit (logical value `1`), but the monitored bus
+    /// value was dominant.
+    Bit1,
+
+    /// The CRC check sum of a received message was incorrect.
+    Crc,
+
+    /// A received frame was lost because the receive buffer was full.
+    Overrun,
+
+    /// The node is bus-off and does not take part in the bus activities anymore.
+    BusOff,
+
+    /// The node lost arbitration while transmitting.
+    ArbitrationLost,
+
+    /// The requested operation or configuration is not supported by the controller.
+    Unsupported,
+
+    /// There are not enough filter banks left for the requested filter configuration.
+    FilterBanksExhausted,
+
+    /// A different error occurred. The original error may contain more information.
+    Other,
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Stuff => write!(f, "A stuff error occurred"),
+            Self::Form => write!(f, "A form error occurred"),
+            Self::Acknowledge => write!(f, "The transmitted frame was not acknowledged"),
+            Self::Bit0 => write!(f, "A dominant bit was monitored as recessive"),
+            Self::Bit1 => write!(f, "A recessive bit was monitored as dominant"),
+            Self::Crc => write!(f, "The CRC of a received frame was incorrect"),
+            Self::Overrun => write!(
+                f,
+                "A received frame was lost because the receive buffer was full"
+            ),
+            Self::BusOff => write!(f, "The node is bus-off"),
+            Self::ArbitrationLost => write!(f, "Arbitration was lost"),
+            Self::Unsupported => write!(f, "The operation is not supported by the controller"),
+            Self::FilterBanksExhausted => write!(f, "Not enough filter banks are available"),
+            Self::Other => write!(
+                f,
+                "A different error occurred. The original error may contain more information"
+            ),
+        }
+    }
+}
+
+/// The fault confinement state of a Can node.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum ErrorState {
+    /// Both error counters are at most 127, the node signals errors with active error flags.
+    Active,
+
+    /// One of the error counters is above 127, the node signals errors with passive error
+    /// flags and waits an additional suspend transmission time between frames.
+    Passive,
+
+    /// The transmit error counter exceeded 255, the node does not take part in bus activities.
+    BusOff,
+}
+
+/// The error counters of a Can node.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ErrorCounters {
+    /// The transmit error counter (TEC).
+    pub transmit: u8,
+
+    /// The receive error counter (REC).
+    pub receive: u8,
+}
+
+/// How a Can node leaves the bus-off state.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum RecoveryMode {
+    /// The node starts the recovery sequence on its own as soon as it enters bus-off.
+    Automatic,
+
+    /// The node stays bus-off until the recovery sequence is started explicitly.
+    Manual,
+}
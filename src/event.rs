@@ -0,0 +1,60 @@
+//! Bus events
+
+use crate::{ErrorKind, ErrorState};
+
+/// Where in a frame an error was detected.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum ErrorLocation {
+    /// In the start of frame bit.
+    StartOfFrame,
+
+    /// In the arbitration field (identifier, RTR, IDE and SRR bits).
+    Arbitration,
+
+    /// In the control field (reserved bits and DLC).
+    Control,
+
+    /// In the data field.
+    Data,
+
+    /// In the CRC sequence or CRC delimiter.
+    Crc,
+
+    /// In the acknowledge slot or acknowledge delimiter.
+    Acknowledge,
+
+    /// In the end of frame field.
+    EndOfFrame,
+
+    /// In the intermission field.
+    Intermission,
+
+    /// The controller does not report where the error was detected.
+    Unknown,
+}
+
+/// An event observed on the bus, see `EventReceiver`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum BusEvent<F> {
+    /// A data or remote frame was received.
+    Frame(F),
+
+    /// An error frame was signaled.
+    ErrorFrame {
+        /// The kind of error that was detected.
+        kind: ErrorKind,
+
+        /// Where in the frame the error was detected.
+        location: ErrorLocation,
+    },
+
+    /// An overload frame was signaled.
+    OverloadFrame,
+
+    /// Received frames were lost because the receive buffer was full.
+    Overrun,
+
+    /// The fault confinement state of the node changed to the given state.
+    StateChange(ErrorState),
+}
@@ -0,0 +1,99 @@
+//! Can-FD data length helpers
+//!
+//! Can-FD keeps the 4-bit DLC of classic Can, but maps the codes `9..=15` to the non-linear
+//! payload lengths 12, 16, 20, 24, 32, 48 and 64. Payloads of any other length must be padded
+//! up to the next valid length before they can be transmitted.
+
+/// The maximum payload length of a Can-FD frame.
+pub const MAX_PAYLOAD_LEN: usize = 64;
+
+/// Payload length for each DLC.
+const DLC_TO_LEN: [u8; 16] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 12, 16, 20, 24, 32, 48, 64];
+
+/// Returns the payload length of a Can-FD frame with the given DLC.
+///
+/// Returns `None` if `dlc` does not fit in 4 bits (`> 15`).
+///
+/// *Note: In classic Can frames the DLCs `9..=15` all mean a payload of 8 bytes.*
+pub const fn dlc_to_len(dlc: u8) -> Option<usize> {
+    if dlc < 16 {
+        Some(DLC_TO_LEN[dlc as usize] as usize)
+    } else {
+        None
+    }
+}
+
+/// Returns the DLC of a Can-FD frame with a payload of `len` bytes.
+///
+/// Returns `None` if `len` is not a valid Can-FD payload length, see `padded_len`.
+pub const fn len_to_dlc(len: usize) -> Option<u8> {
+    let mut dlc = 0;
+    while dlc < DLC_TO_LEN.len() {
+        if DLC_TO_LEN[dlc] as usize == len {
+            return Some(dlc as u8);
+        }
+        dlc += 1;
+    }
+    None
+}
+
+/// Returns the smallest valid Can-FD payload length that can hold `len` bytes.
+///
+/// Returns `None` if `len` is larger than 64.
+pub const fn padded_len(len: usize) -> Option<usize> {
+    let mut dlc = 0;
+    while dlc < DLC_TO_LEN.len() {
+        if DLC_TO_LEN[dlc] as usize >= len {
+            return Some(DLC_TO_LEN[dlc] as usize);
+        }
+        dlc += 1;
+    }
+    None
+}
+
+/// Copies `data` into `buf`, padded with `padding` bytes up to the next valid Can-FD length.
+///
+/// Returns the padded payload, or `None` if `data` is longer than 64 bytes.
+pub fn pad<'a>(data: &[u8], buf: &'a mut [u8; MAX_PAYLOAD_LEN], padding: u8) -> Option<&'a [u8]> {
+    let len = padded_len(data.len())?;
+    buf[..data.len()].copy_from_slice(data);
+    for byte in &mut buf[data.len()..len] {
+        *byte = padding;
+    }
+    Some(&buf[..len])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dlc_lengths() {
+        for dlc in 0..16 {
+            let len = dlc_to_len(dlc).unwrap();
+            assert_eq!(len_to_dlc(len), Some(dlc));
+        }
+        assert_eq!(dlc_to_len(9), Some(12));
+        assert_eq!(dlc_to_len(15), Some(64));
+        assert_eq!(dlc_to_len(16), None);
+        assert_eq!(len_to_dlc(9), None);
+        assert_eq!(len_to_dlc(65), None);
+    }
+
+    #[test]
+    fn padding() {
+        assert_eq!(padded_len(0), Some(0));
+        assert_eq!(padded_len(8), Some(8));
+        assert_eq!(padded_len(9), Some(12));
+        assert_eq!(padded_len(33), Some(48));
+        assert_eq!(padded_len(64), Some(64));
+        assert_eq!(padded_len(65), None);
+
+        let mut buf = [0; MAX_PAYLOAD_LEN];
+        assert_eq!(
+            pad(&[1, 2, 3, 4, 5, 6, 7, 8, 9], &mut buf, 0xCC),
+            Some(&[1, 2, 3, 4, 5, 6, 7, 8, 9, 0xCC, 0xCC, 0xCC][..])
+        );
+        assert_eq!(pad(&[0; 65], &mut buf, 0), None);
+    }
+}
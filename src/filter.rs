@@ -0,0 +1,736 @@
+//! Filter configuration helpers
+
+use core::marker::PhantomData;
+
+use crate::{AnyId, Filter, Id};
+
+/// How a filter bank matches the identifiers of received frames.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum FilterBankMode<'a, F, I> {
+    /// Accept the frames matching a `Filter`.
+    Mask(F),
+
+    /// Accept the frames with one of the listed identifiers.
+    List(&'a [I]),
+}
+
+/// The configuration of a single filter bank, see `FilterBanks`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct FilterBankConfig<'a, F, I> {
+    /// How the bank matches identifiers.
+    pub mode: FilterBankMode<'a, F, I>,
+
+    /// The index of the receive FIFO that frames accepted by this bank are stored in.
+    pub fifo: usize,
+}
+
+/// How closely a filter matches the requested set of identifiers.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Fit<F> {
+    /// The filter accepts exactly the requested identifiers.
+    Exact(F),
+
+    /// The filter accepts the requested identifiers, and some others.
+    Superset(F),
+}
+
+impl<F> Fit<F> {
+    /// Returns true if the filter accepts exactly the requested identifiers.
+    pub fn is_exact(&self) -> bool {
+        matches!(self, Fit::Exact(_))
+    }
+
+    /// Returns the filter, whether it is exact or not.
+    pub fn into_inner(self) -> F {
+        match self {
+            Fit::Exact(filter) | Fit::Superset(filter) => filter,
+        }
+    }
+
+    /// Converts the filter, keeping whether it is exact.
+    pub fn map<G>(self, f: impl FnOnce(F) -> G) -> Fit<G> {
+        match self {
+            Fit::Exact(filter) => Fit::Exact(f(filter)),
+            Fit::Superset(filter) => Fit::Superset(f(filter)),
+        }
+    }
+}
+
+/// Bits matching a base id, or the low bits of an extended id.
+const LOW_BITS: u32 = 0x7FF;
+
+/// Bits matching an extended id.
+const EXTENDED_BITS: u32 = 0x1FFF_FFFF;
+
+/// Bit matching the extended frame flag.
+const EXTENDED_FLAG: u32 = 1 << 29;
+
+/// A mask/filter pair, with the semantics of `Filter::from_mask`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct MaskFilter {
+    /// The bits of an identifier that must match `filter`.
+    pub mask: u32,
+
+    /// The expected value of the bits selected by `mask`.
+    pub filter: u32,
+}
+
+impl MaskFilter {
+    /// Creates a `MaskFilter` accepting exactly `id`.
+    pub fn from_id(id: AnyId) -> Self {
+        match id {
+            AnyId::Standard(id) => MaskFilter {
+                mask: EXTENDED_FLAG | LOW_BITS,
+                filter: id.as_raw() as u32,
+            },
+            AnyId::Extended(id) => MaskFilter {
+                mask: EXTENDED_FLAG | EXTENDED_BITS,
+                filter: EXTENDED_FLAG | id.as_raw(),
+            },
+        }
+    }
+
+    /// Creates the most specific `MaskFilter` accepting all identifiers from `first` to `last`.
+    ///
+    /// The result is exact if the range is an aligned block of a power of two identifiers,
+    /// otherwise it is the smallest such block containing the range.
+    ///
+    /// ### Panic
+    /// Panics if `first` and `last` are not of the same kind, or if `first` is above `last`.
+    pub fn from_range(first: AnyId, last: AnyId) -> Fit<Self> {
+        let (first, last, bits, flag) = match (first, last) {
+            (AnyId::Standard(first), AnyId::Standard(last)) => {
+                (first.as_raw() as u32, last.as_raw() as u32, LOW_BITS, 0)
+            }
+            (AnyId::Extended(first), AnyId::Extended(last)) => {
+                (first.as_raw(), last.as_raw(), EXTENDED_BITS, EXTENDED_FLAG)
+            }
+            _ => panic!("range bounds must be of the same kind"),
+        };
+        assert!(first <= last, "range start must not be above its end");
+
+        // Only the bits above the most significant differing bit are common to the whole range.
+        let varying = match first ^ last {
+            0 => 0,
+            differ => u32::MAX >> differ.leading_zeros(),
+        };
+        let filter = MaskFilter {
+            mask: EXTENDED_FLAG | (bits & !varying),
+            filter: flag | (first & !varying),
+        };
+
+        if first & varying == 0 && last & varying == varying {
+            Fit::Exact(filter)
+        } else {
+            Fit::Superset(filter)
+        }
+    }
+
+    /// Creates the most specific `MaskFilter` accepting all identifiers in `ids`.
+    ///
+    /// The result is exact if it accepts no other identifier.
+    ///
+    /// ### Panic
+    /// Panics if `ids` is empty.
+    pub fn from_list<I: Id>(ids: &[I]) -> Fit<Self> {
+        let (first, rest) = ids.split_first().expect("id list must not be empty");
+        let filter = rest
+            .iter()
+            .fold(MaskFilter::from_id(first.to_any_id()), |filter, id| {
+                filter.merge(&MaskFilter::from_id(id.to_any_id()))
+            });
+
+        let distinct = (0..ids.len())
+            .filter(|&index| {
+                let id = ids[index].to_any_id();
+                !ids[..index].iter().any(|other| other.to_any_id() == id)
+            })
+            .count();
+        if filter.accepted_count() == distinct as u64 {
+            Fit::Exact(filter)
+        } else {
+            Fit::Superset(filter)
+        }
+    }
+
+    /// Converts this mask/filter pair into a `Filter` using `Filter::from_mask`.
+    pub fn to_filter<F: Filter>(&self) -> F {
+        F::from_mask(self.mask, self.filter)
+    }
+
+    /// Returns true if frames with the identifier `id` are accepted.
+    pub fn matches(&self, id: AnyId) -> bool {
+        match id {
+            AnyId::Standard(id) => {
+                self.accepts_base()
+                    && (id.as_raw() as u32 ^ self.filter) & self.mask & LOW_BITS == 0
+            }
+            AnyId::Extended(id) => {
+                self.accepts_extended()
+                    && (id.as_raw() ^ self.filter) & self.mask & EXTENDED_BITS == 0
+            }
+        }
+    }
+
+    fn accepts_base(&self) -> bool {
+        self.mask & EXTENDED_FLAG == 0 || self.filter & EXTENDED_FLAG == 0
+    }
+
+    fn accepts_extended(&self) -> bool {
+        self.mask & EXTENDED_FLAG == 0 || self.filter & EXTENDED_FLAG != 0
+    }
+
+    /// Returns the number of identifiers accepted.
+    fn accepted_count(&self) -> u64 {
+        let mut count = 0;
+        if self.accepts_base() {
+            count += 1 << (11 - (self.mask & LOW_BITS).count_ones());
+        }
+        if self.accepts_extended() {
+            count += 1 << (29 - (self.mask & EXTENDED_BITS).count_ones());
+        }
+        count
+    }
+
+    /// Returns true if an identifier accepted by `self` is also accepted by `other`.
+    fn intersects(&self, other: &MaskFilter) -> bool {
+        let differ = (self.filter ^ other.filter) & self.mask & other.mask;
+        (self.accepts_base() && other.accepts_base() && differ & LOW_BITS == 0)
+            || (self.accepts_extended() && other.accepts_extended() && differ & EXTENDED_BITS == 0)
+    }
+
+    /// Returns the most specific `MaskFilter` accepting all identifiers accepted by `self`
+    /// and `other`.
+    fn merge(&self, other: &MaskFilter) -> MaskFilter {
+        let agree = self.mask & other.mask & !(self.filter ^ other.filter);
+        let mut mask = agree & LOW_BITS;
+
+        // The bits above the base id only matter for extended ids.
+        let high = EXTENDED_BITS & !LOW_BITS;
+        mask |= match (self.accepts_extended(), other.accepts_extended()) {
+            (true, true) => agree & high,
+            (true, false) => self.mask & high,
+            (false, _) => other.mask & high,
+        };
+        let source = if self.accepts_extended() { self } else { other };
+        let mut filter = (self.filter & mask & LOW_BITS) | (source.filter & mask & high);
+
+        // Keep matching the extended frame flag if both accept a single kind of identifiers.
+        let base_only = !self.accepts_extended() && !other.accepts_extended();
+        let extended_only = !self.accepts_base() && !other.accepts_base();
+        if base_only || extended_only {
+            mask |= EXTENDED_FLAG;
+        }
+        if extended_only {
+            filter |= EXTENDED_FLAG;
+        }
+
+        MaskFilter { mask, filter }
+    }
+}
+
+/// An error returned by `compile`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum CompileError {
+    /// The output buffer can not hold one `MaskFilter` per accepted identifier.
+    BufferTooSmall,
+
+    /// No filter bank is available, but some identifiers must be accepted.
+    NoBanks,
+}
+
+/// The result of `compile`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Compilation {
+    /// The number of `MaskFilter`s written to the output buffer.
+    pub len: usize,
+
+    /// The number of identifiers accepted by the `MaskFilter`s that were neither to be
+    /// accepted nor tolerated.
+    pub over_acceptance: u64,
+}
+
+/// Compiles a set of identifiers into at most `banks` mask/filter pairs accepting all of them.
+///
+/// Identifiers in `tolerate` may be accepted as well, without counting as over-acceptance.
+/// The compiled `MaskFilter`s are written to the start of `out`, which must be able to hold
+/// one `MaskFilter` per distinct identifier in `accept` and in `tolerate`, the rest of `out`
+/// being used as scratch space. The compiled filters never overlap, and can be turned into
+/// `Filter`s with `MaskFilter::to_filter`.
+///
+/// The compilation starts with one exact `MaskFilter` per identifier, and greedily merges pairs
+/// of filters until the budget is met. A merge also absorbs the filters overlapping the merged
+/// one, and the merge accepting the fewest additional identifiers per filter it saves is
+/// picked first. This does not always find the smallest possible over-acceptance.
+///
+/// Each merge evaluates every pair of remaining filters against all others, so compiling `n`
+/// distinct identifiers with `t` tolerated ones takes O(n³·(n + t)) time. This is meant for
+/// the identifiers of a single node: keep `accept` and `tolerate` to about 64 identifiers in
+/// total when compiling at run time, and compile larger sets ahead of time.
+pub fn compile<I: Id>(
+    accept: &[I],
+    tolerate: &[I],
+    banks: usize,
+    out: &mut [MaskFilter],
+) -> Result<Compilation, CompileError> {
+    let mut len = push_distinct(out, 0, accept)?;
+    let accepted = len;
+
+    if len > 0 && banks == 0 {
+        return Err(CompileError::NoBanks);
+    }
+
+    // The distinct tolerated identifiers that are not accepted, as exact filters stored after
+    // the accepted ones. Merging only shrinks `len`, so they are never overwritten.
+    let tolerated_end = push_distinct(out, accepted, tolerate)?;
+    let (out, tolerated) = out.split_at_mut(accepted);
+    let tolerated = &tolerated[..tolerated_end - accepted];
+
+    while len > banks {
+        // The best merge adds the fewest identifiers per filter it saves, only counting the
+        // savings still needed to meet the budget, and saves the most filters on a tie.
+        let needed = (len - banks) as u64;
+        let mut best: Option<(MaskFilter, u64, u64)> = None;
+        for i in 0..len {
+            for j in i + 1..len {
+                let merged = absorb(&out[..len], out[i].merge(&out[j]));
+                let (cost, saved) = merge_cost(&out[..len], tolerated, &merged);
+                let saved = saved.min(needed);
+                let better = match best {
+                    Some((_, best_cost, best_saved)) => {
+                        let (lhs, rhs) = (cost * best_saved, best_cost * saved);
+                        lhs < rhs || (lhs == rhs && saved > best_saved)
+                    }
+                    None => true,
+                };
+                if better {
+                    best = Some((merged, cost, saved));
+                }
+            }
+        }
+
+        let merged = match best {
+            Some((merged, _, _)) => merged,
+            None => break,
+        };
+        let mut index = 0;
+        while index < len {
+            if merged.intersects(&out[index]) {
+                len -= 1;
+                out[index] = out[len];
+            } else {
+                index += 1;
+            }
+        }
+        out[len] = merged;
+        len += 1;
+    }
+
+    let filters = &out[..len];
+    let total: u64 = filters.iter().map(MaskFilter::accepted_count).sum();
+    let covered = tolerated
+        .iter()
+        .filter(|id| filters.iter().any(|filter| filter.intersects(id)))
+        .count();
+
+    Ok(Compilation {
+        len,
+        over_acceptance: total - accepted as u64 - covered as u64,
+    })
+}
+
+/// Writes an exact `MaskFilter` for each identifier in `ids` to `out`, starting at `len`,
+/// skipping the identifiers already matched by `out[..len]`. Returns the new length.
+fn push_distinct<I: Id>(
+    out: &mut [MaskFilter],
+    mut len: usize,
+    ids: &[I],
+) -> Result<usize, CompileError> {
+    for id in ids {
+        let id = id.to_any_id();
+        if out[..len].iter().any(|filter| filter.matches(id)) {
+            continue;
+        }
+        let slot = out.get_mut(len).ok_or(CompileError::BufferTooSmall)?;
+        *slot = MaskFilter::from_id(id);
+        len += 1;
+    }
+    Ok(len)
+}
+
+/// Grows `merged` until it overlaps none of `filters` partially, by merging it with the
+/// filters it overlaps.
+fn absorb(filters: &[MaskFilter], mut merged: MaskFilter) -> MaskFilter {
+    loop {
+        let grown = filters
+            .iter()
+            .filter(|filter| merged.intersects(filter))
+            .fold(merged, |grown, filter| grown.merge(filter));
+        if grown == merged {
+            return merged;
+        }
+        merged = grown;
+    }
+}
+
+/// Returns the number of identifiers `merged` accepts that are neither accepted by the
+/// `filters` it replaces nor `tolerated`, and the number of filters it saves.
+///
+/// `merged` must contain every filter it overlaps, as returned by `absorb`.
+fn merge_cost(filters: &[MaskFilter], tolerated: &[MaskFilter], merged: &MaskFilter) -> (u64, u64) {
+    let count_tolerated =
+        |filter: &MaskFilter| tolerated.iter().filter(|id| filter.intersects(id)).count() as u64;
+
+    // The tolerated identifiers already accepted are accepted by one of the replaced filters.
+    let mut cost = merged.accepted_count() - count_tolerated(merged);
+    let mut replaced = 0;
+    for filter in filters.iter().filter(|filter| merged.intersects(filter)) {
+        cost -= filter.accepted_count() - count_tolerated(filter);
+        replaced += 1;
+    }
+    (cost, replaced - 1)
+}
+
+/// A `Filter` evaluated in software.
+///
+/// Implements the mask semantics documented on `Filter::from_mask`, and can be used to
+/// post-filter received frames that slipped through a hardware filter.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct SoftwareFilter<I> {
+    mask_filter: MaskFilter,
+    _id: PhantomData<I>,
+}
+
+impl<I> SoftwareFilter<I> {
+    /// Returns the mask/filter pair of this filter.
+    pub fn mask_filter(&self) -> MaskFilter {
+        self.mask_filter
+    }
+}
+
+impl<I> From<MaskFilter> for SoftwareFilter<I> {
+    fn from(mask_filter: MaskFilter) -> Self {
+        SoftwareFilter {
+            mask_filter,
+            _id: PhantomData,
+        }
+    }
+}
+
+impl<I: Id> Filter for SoftwareFilter<I> {
+    type Id = I;
+
+    fn from_id(id: I) -> Self {
+        MaskFilter::from_id(id.to_any_id()).into()
+    }
+
+    fn accept_all() -> Self {
+        MaskFilter { mask: 0, filter: 0 }.into()
+    }
+
+    fn from_mask(mask: u32, filter: u32) -> Self {
+        assert!(
+            mask & !(EXTENDED_FLAG | EXTENDED_BITS) == 0,
+            "mask bits 30..32 must be 0"
+        );
+        MaskFilter { mask, filter }.into()
+    }
+
+    fn matches_id(&self, id: &I) -> bool {
+        self.mask_filter.matches(id.to_any_id())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{extended, standard};
+    use crate::{ClassicFrame, ExtendedId, Frame, StandardId};
+
+    /// Checks `compilation` against a brute force evaluation of the compiled `filters`, which
+    /// must only accept standard and extended identifiers below 0x800.
+    fn check(
+        accept: &[AnyId],
+        tolerate: &[AnyId],
+        filters: &[MaskFilter],
+        compilation: Compilation,
+    ) {
+        let mut over_acceptance = 0;
+        let mut counts = [0u64; 64];
+        for raw in 0..0x800 {
+            for id in [standard(raw as u16), extended(raw)] {
+                let matching = filters.iter().filter(|filter| filter.matches(id)).count();
+                assert!(matching <= 1, "filters overlap on {:?}", id);
+                for (count, filter) in counts.iter_mut().zip(filters) {
+                    *count += filter.matches(id) as u64;
+                }
+
+                if accept.contains(&id) {
+                    assert_eq!(matching, 1, "{:?} is not accepted", id);
+                } else if matching == 1 && !tolerate.contains(&id) {
+                    over_acceptance += 1;
+                }
+            }
+        }
+
+        for (count, filter) in counts.iter().zip(filters) {
+            assert_eq!(
+                *count,
+                filter.accepted_count(),
+                "{:?} accepts ids above 0x800",
+                filter
+            );
+        }
+        assert_eq!(compilation.over_acceptance, over_acceptance);
+    }
+
+    #[test]
+    fn compile_meets_the_budget() {
+        let accept: [AnyId; 40] = core::array::from_fn(|i| standard(0x200 + 3 * i as u16));
+        let mut out = [MaskFilter { mask: 0, filter: 0 }; 40];
+
+        let mut previous = u64::MAX;
+        for banks in 1..=40 {
+            let compilation = compile(&accept, &[], banks, &mut out).unwrap();
+            assert!(compilation.len <= banks);
+            assert!(compilation.over_acceptance <= previous);
+            check(&accept, &[], &out[..compilation.len], compilation);
+            previous = compilation.over_acceptance;
+        }
+
+        // One 0x200-0x23F block and 18 exact filters over-accept 42 identifiers.
+        let compilation = compile(&accept, &[], 19, &mut out).unwrap();
+        assert_eq!(compilation.len, 19);
+        assert_eq!(compilation.over_acceptance, 42);
+
+        let compilation = compile(&accept, &[], 40, &mut out).unwrap();
+        assert_eq!(compilation.len, 40);
+        assert_eq!(compilation.over_acceptance, 0);
+    }
+
+    #[test]
+    fn compile_many_ids() {
+        let accept: [AnyId; 64] = core::array::from_fn(|i| standard(7 * i as u16 + 5));
+        let mut out = [MaskFilter { mask: 0, filter: 0 }; 64];
+
+        for banks in [14, 32] {
+            let compilation = compile(&accept, &[], banks, &mut out).unwrap();
+            assert!(compilation.len <= banks);
+            check(&accept, &[], &out[..compilation.len], compilation);
+        }
+    }
+
+    #[test]
+    fn compile_uses_tolerated_ids() {
+        let accept = [standard(0x100), standard(0x103)];
+        let tolerate = [
+            standard(0x101),
+            standard(0x102),
+            standard(0x101),
+            standard(0x100),
+        ];
+        let mut out = [MaskFilter { mask: 0, filter: 0 }; 5];
+
+        let compilation = compile(&accept, &tolerate, 1, &mut out).unwrap();
+        assert_eq!(compilation.len, 1);
+        assert_eq!(compilation.over_acceptance, 0);
+        check(&accept, &tolerate, &out[..1], compilation);
+    }
+
+    #[test]
+    fn compile_mixed_ids() {
+        let accept = [
+            standard(0x10),
+            standard(0x11),
+            standard(0x7F0),
+            extended(0x10),
+            extended(0x13),
+            extended(0x400),
+        ];
+        let tolerate = [extended(0x11), standard(0x7F1)];
+        let mut out = [MaskFilter { mask: 0, filter: 0 }; 8];
+
+        for banks in 1..=6 {
+            let compilation = compile(&accept, &tolerate, banks, &mut out).unwrap();
+            assert!(compilation.len <= banks);
+            check(&accept, &tolerate, &out[..compilation.len], compilation);
+        }
+
+        // A filter ignoring the extended frame flag accepts both 0x10 identifiers exactly.
+        let compilation = compile(&accept, &tolerate, 5, &mut out).unwrap();
+        assert_eq!(compilation.over_acceptance, 0);
+    }
+
+    #[test]
+    fn compile_errors() {
+        let accept = [standard(1), standard(2), standard(1)];
+        let tolerate = [standard(3)];
+        let mut out = [MaskFilter { mask: 0, filter: 0 }; 2];
+
+        assert_eq!(
+            compile(&accept, &[], 0, &mut out),
+            Err(CompileError::NoBanks)
+        );
+        assert_eq!(
+            compile(&accept, &tolerate, 1, &mut out),
+            Err(CompileError::BufferTooSmall)
+        );
+        assert_eq!(
+            compile(&accept, &[], 1, &mut out[..1]),
+            Err(CompileError::BufferTooSmall)
+        );
+        assert!(compile(&accept, &[], 1, &mut out).is_ok());
+    }
+
+    #[test]
+    fn range_fit() {
+        assert_eq!(
+            MaskFilter::from_range(standard(0x100), standard(0x1FF)),
+            Fit::Exact(MaskFilter {
+                mask: EXTENDED_FLAG | 0x700,
+                filter: 0x100,
+            })
+        );
+        assert_eq!(
+            MaskFilter::from_range(extended(0x10), extended(0x17)),
+            Fit::Exact(MaskFilter {
+                mask: EXTENDED_FLAG | (EXTENDED_BITS & !0x7),
+                filter: EXTENDED_FLAG | 0x10,
+            })
+        );
+        assert_eq!(
+            MaskFilter::from_range(standard(0x101), standard(0x102)),
+            Fit::Superset(MaskFilter {
+                mask: EXTENDED_FLAG | 0x7FC,
+                filter: 0x100,
+            })
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "range bounds must be of the same kind")]
+    fn range_of_mixed_kinds() {
+        MaskFilter::from_range(standard(0x10), extended(0x10));
+    }
+
+    #[test]
+    fn list_fit() {
+        let exact = Fit::Exact(MaskFilter {
+            mask: EXTENDED_FLAG | 0x7FE,
+            filter: 0x10,
+        });
+        assert_eq!(
+            MaskFilter::from_list(&[standard(0x11), standard(0x10)]),
+            exact
+        );
+        assert_eq!(
+            MaskFilter::from_list(&[standard(0x10), standard(0x11), standard(0x10)]),
+            exact
+        );
+        assert_eq!(
+            MaskFilter::from_list(&[extended(0x1234)]),
+            Fit::Exact(MaskFilter::from_id(extended(0x1234)))
+        );
+        assert_eq!(
+            MaskFilter::from_list(&[standard(0x10), standard(0x13), standard(0x13)]),
+            Fit::Superset(MaskFilter {
+                mask: EXTENDED_FLAG | 0x7FC,
+                filter: 0x10,
+            })
+        );
+    }
+
+    #[test]
+    fn list_of_mixed_kinds() {
+        // Ignoring the extended frame flag accepts both identifiers exactly.
+        let fit = MaskFilter::from_list(&[standard(0x10), extended(0x10)]);
+        assert!(fit.is_exact());
+        let filter = fit.into_inner();
+        assert!(filter.matches(standard(0x10)));
+        assert!(filter.matches(extended(0x10)));
+        assert!(!filter.matches(extended(0x1000_0010)));
+
+        let fit = MaskFilter::from_list(&[standard(0x10), extended(0x11)]);
+        assert!(!fit.is_exact());
+        assert!(fit.into_inner().matches(standard(0x11)));
+    }
+
+    #[test]
+    #[should_panic(expected = "id list must not be empty")]
+    fn empty_list() {
+        MaskFilter::from_list::<AnyId>(&[]);
+    }
+
+    #[test]
+    fn filter_from_range_and_list() {
+        let fit = SoftwareFilter::<AnyId>::from_range(standard(0x100), standard(0x1FF));
+        assert_eq!(
+            fit.map(|filter| filter.mask_filter()),
+            MaskFilter::from_range(standard(0x100), standard(0x1FF))
+        );
+
+        let fit = SoftwareFilter::<AnyId>::from_list(&[standard(0x10), standard(0x13)]);
+        assert!(!fit.is_exact());
+        let filter = fit.into_inner();
+        assert!(filter.matches_id(&standard(0x12)));
+        assert!(!filter.matches_id(&standard(0x14)));
+
+        let fit = SoftwareFilter::<AnyId>::from_list(&[extended(0x10), extended(0x10)]);
+        assert_eq!(fit, Fit::Exact(SoftwareFilter::from_id(extended(0x10))));
+    }
+
+    #[test]
+    #[should_panic(expected = "range bounds must be of the same kind")]
+    fn filter_from_range_of_mixed_kinds() {
+        SoftwareFilter::<AnyId>::from_range(extended(0x10), standard(0x10));
+    }
+
+    #[test]
+    #[should_panic(expected = "id list must not be empty")]
+    fn filter_from_empty_list() {
+        SoftwareFilter::<AnyId>::from_list(&[]);
+    }
+
+    #[test]
+    fn software_filter_extended_flag() {
+        let filter = SoftwareFilter::from_mask(EXTENDED_FLAG | 0x7FF, EXTENDED_FLAG | 0x123);
+        assert!(filter.matches_id(&extended(0x123)));
+        assert!(filter.matches_id(&extended(0x1000_0123)));
+        assert!(!filter.matches_id(&standard(0x123)));
+
+        let filter = SoftwareFilter::from_mask(EXTENDED_FLAG | 0x7FF, 0x123);
+        assert!(filter.matches_id(&standard(0x123)));
+        assert!(!filter.matches_id(&extended(0x123)));
+
+        let filter = SoftwareFilter::from_mask(0x7FF, 0x123);
+        assert!(filter.matches_id(&standard(0x123)));
+        assert!(filter.matches_id(&extended(0x123)));
+        assert!(!filter.matches_id(&extended(0x124)));
+    }
+
+    #[test]
+    fn software_filter_ignores_high_bits_for_base_ids() {
+        let filter = SoftwareFilter::from_mask(EXTENDED_BITS, 0x1234_5123);
+        assert!(filter.matches_id(&standard(0x123)));
+        assert!(!filter.matches_id(&standard(0x124)));
+        assert!(filter.matches_id(&extended(0x1234_5123)));
+        assert!(!filter.matches_id(&extended(0x0234_5123)));
+    }
+
+    #[test]
+    #[should_panic(expected = "mask bits 30..32 must be 0")]
+    fn software_filter_reserved_bits() {
+        SoftwareFilter::<AnyId>::from_mask(1 << 30, 0);
+    }
+
+    #[test]
+    fn filter_matches_frame() {
+        let base = ClassicFrame::new(StandardId::new(0x10).unwrap(), &[]).unwrap();
+        let extended = ClassicFrame::new(ExtendedId::new(0x10).unwrap(), &[]).unwrap();
+
+        let filter = SoftwareFilter::from_id(standard(0x10));
+        assert!(filter.matches(&base));
+        assert!(!filter.matches(&extended));
+        assert!(SoftwareFilter::<AnyId>::accept_all().matches(&extended));
+    }
+}
@@ -0,0 +1,195 @@
+//! Concrete CAN frames
+
+use crate::{AnyId, Frame};
+
+/// The maximum payload length of a classic Can Frame.
+const MAX_DLC: usize = 8;
+
+/// An error returned when building a `ClassicFrame`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum FrameError {
+    /// The payload of a data frame is longer than 8 bytes.
+    PayloadTooLong,
+
+    /// The DLC of a remote frame is larger than 8.
+    DlcOutOfRange,
+}
+
+/// An owned classic (non Can-FD) Can Frame.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct ClassicFrame {
+    id: AnyId,
+    remote: bool,
+    dlc: u8,
+    data: [u8; MAX_DLC],
+}
+
+impl ClassicFrame {
+    /// Starts building a `ClassicFrame` with the given identifier.
+    ///
+    /// Unless configured otherwise, the builder produces a data frame with an empty payload.
+    pub fn builder(id: impl Into<AnyId>) -> ClassicFrameBuilder {
+        ClassicFrameBuilder {
+            id: id.into(),
+            remote: false,
+            len: 0,
+            data: [0; MAX_DLC],
+        }
+    }
+}
+
+impl Frame for ClassicFrame {
+    type Id = AnyId;
+
+    fn new(id: impl Into<AnyId>, data: &[u8]) -> Option<Self> {
+        ClassicFrame::builder(id).data(data).build().ok()
+    }
+
+    fn new_remote(id: impl Into<AnyId>, dlc: usize) -> Option<Self> {
+        ClassicFrame::builder(id).remote(dlc).build().ok()
+    }
+
+    fn is_remote_frame(&self) -> bool {
+        self.remote
+    }
+
+    fn is_data_frame(&self) -> bool {
+        !self.remote
+    }
+
+    fn id(&self) -> AnyId {
+        self.id
+    }
+
+    fn dlc(&self) -> usize {
+        self.dlc as usize
+    }
+
+    fn data(&self) -> Option<&[u8]> {
+        if self.remote {
+            None
+        } else {
+            Some(&self.data[..self.dlc as usize])
+        }
+    }
+}
+
+/// A builder for `ClassicFrame`, created by `ClassicFrame::builder`.
+#[derive(Debug, Clone)]
+pub struct ClassicFrameBuilder {
+    id: AnyId,
+    remote: bool,
+    len: usize,
+    data: [u8; MAX_DLC],
+}
+
+impl ClassicFrameBuilder {
+    /// Makes the frame a data frame carrying `data`.
+    ///
+    /// The length is validated by `build`.
+    pub fn data(mut self, data: &[u8]) -> Self {
+        let copied = data.len().min(MAX_DLC);
+        self.data = [0; MAX_DLC];
+        self.data[..copied].copy_from_slice(&data[..copied]);
+        self.len = data.len();
+        self.remote = false;
+        self
+    }
+
+    /// Makes the frame a remote frame requesting `dlc` bytes.
+    ///
+    /// The DLC is validated by `build`.
+    pub fn remote(mut self, dlc: usize) -> Self {
+        self.data = [0; MAX_DLC];
+        self.len = dlc;
+        self.remote = true;
+        self
+    }
+
+    /// Builds the `ClassicFrame`.
+    ///
+    /// Fails if the payload is longer than 8 bytes, or the DLC of a remote frame is larger than 8.
+    pub fn build(self) -> Result<ClassicFrame, FrameError> {
+        if self.len > MAX_DLC {
+            return Err(if self.remote {
+                FrameError::DlcOutOfRange
+            } else {
+                FrameError::PayloadTooLong
+            });
+        }
+
+        Ok(ClassicFrame {
+            id: self.id,
+            remote: self.remote,
+            dlc: self.len as u8,
+            data: self.data,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ExtendedId, StandardId};
+
+    #[test]
+    fn data_frame() {
+        let id = StandardId::new(0x123).unwrap();
+        let frame = ClassicFrame::new(id, &[1, 2, 3]).unwrap();
+        assert!(frame.is_data_frame());
+        assert!(frame.is_base_id_frame());
+        assert_eq!(frame.id(), AnyId::Standard(id));
+        assert_eq!(frame.dlc(), 3);
+        assert_eq!(frame.data(), Some(&[1, 2, 3][..]));
+
+        assert!(ClassicFrame::new(id, &[0; 8]).is_some());
+        assert_eq!(
+            ClassicFrame::builder(id).data(&[0; 9]).build(),
+            Err(FrameError::PayloadTooLong)
+        );
+    }
+
+    #[test]
+    fn remote_frame() {
+        let id = ExtendedId::new(0x1234).unwrap();
+        let frame = ClassicFrame::new_remote(id, 4).unwrap();
+        assert!(frame.is_remote_frame());
+        assert!(frame.is_extended_id_frame());
+        assert_eq!(frame.dlc(), 4);
+        assert_eq!(frame.data(), None);
+
+        assert_eq!(
+            ClassicFrame::builder(id).remote(9).build(),
+            Err(FrameError::DlcOutOfRange)
+        );
+    }
+
+    #[test]
+    fn builder_last_kind_wins() {
+        let id = StandardId::new(0x10).unwrap();
+        let frame = ClassicFrame::builder(id)
+            .remote(9)
+            .data(&[7])
+            .build()
+            .unwrap();
+        assert_eq!(frame.data(), Some(&[7][..]));
+
+        let frame = ClassicFrame::builder(id)
+            .data(&[7])
+            .remote(2)
+            .build()
+            .unwrap();
+        assert_eq!(frame, ClassicFrame::new_remote(id, 2).unwrap());
+    }
+
+    #[test]
+    fn priority() {
+        let id = StandardId::new(0x10).unwrap();
+        let data = ClassicFrame::new(id, &[]).unwrap();
+        let remote = ClassicFrame::new_remote(id, 0).unwrap();
+        assert!(data.priority_cmp(&remote).is_lt());
+
+        let extended = ClassicFrame::new(ExtendedId::new(0x10 << 18).unwrap(), &[]).unwrap();
+        assert!(remote.priority_cmp(&extended).is_lt());
+    }
+}
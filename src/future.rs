@@ -0,0 +1,147 @@
+//! Futures adapting the non-blocking (`nb`) methods to their async counterparts
+//!
+//! These futures poll the `nb` method every time they are polled, and wake themselves up
+//! immediately when it would block. They are meant for implementations that have no interrupt
+//! to wake a task with, at the cost of keeping the executor busy.
+
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use crate::{BusEvent, EventReceiver, Receiver, Transmitter};
+
+/// A future transmitting a `Frame` by polling `Transmitter::try_transmit`.
+pub struct PollTransmit<'a, T: Transmitter> {
+    transmitter: &'a mut T,
+    frame: &'a T::Frame,
+}
+
+impl<'a, T: Transmitter> PollTransmit<'a, T> {
+    /// Creates a future transmitting `frame` through `transmitter`.
+    pub fn new(transmitter: &'a mut T, frame: &'a T::Frame) -> Self {
+        PollTransmit { transmitter, frame }
+    }
+}
+
+impl<'a, T: Transmitter> Future for PollTransmit<'a, T> {
+    type Output = Result<Option<T::Frame>, T::Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        match this.transmitter.try_transmit(this.frame) {
+            Ok(replaced) => Poll::Ready(Ok(replaced)),
+            Err(nb::Error::Other(e)) => Poll::Ready(Err(e)),
+            Err(nb::Error::WouldBlock) => {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// A future receiving a `Frame` by polling `Receiver::try_receive`.
+pub struct PollReceive<'a, R: Receiver> {
+    receiver: &'a mut R,
+}
+
+impl<'a, R: Receiver> PollReceive<'a, R> {
+    /// Creates a future receiving a `Frame` from `receiver`.
+    pub fn new(receiver: &'a mut R) -> Self {
+        PollReceive { receiver }
+    }
+}
+
+impl<'a, R: Receiver> Future for PollReceive<'a, R> {
+    type Output = Result<R::Frame, R::Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.get_mut().receiver.try_receive() {
+            Ok(frame) => Poll::Ready(Ok(frame)),
+            Err(nb::Error::Other(e)) => Poll::Ready(Err(e)),
+            Err(nb::Error::WouldBlock) => {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+    }
+}
+
+/// A future receiving a `BusEvent` by polling `EventReceiver::try_receive_event`.
+pub struct PollReceiveEvent<'a, R: EventReceiver> {
+    receiver: &'a mut R,
+}
+
+impl<'a, R: EventReceiver> PollReceiveEvent<'a, R> {
+    /// Creates a future receiving a `BusEvent` from `receiver`.
+    pub fn new(receiver: &'a mut R) -> Self {
+        PollReceiveEvent { receiver }
+    }
+}
+
+impl<'a, R: EventReceiver> Future for PollReceiveEvent<'a, R> {
+    type Output = Result<BusEvent<R::Frame>, R::Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        match self.get_mut().receiver.try_receive_event() {
+            Ok(event) => Poll::Ready(Ok(event)),
+            Err(nb::Error::Other(e)) => Poll::Ready(Err(e)),
+            Err(nb::Error::WouldBlock) => {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::Frames;
+    use crate::test_utils::{frame, poll_once, Mailboxes};
+    use crate::ErrorKind;
+
+    #[test]
+    fn poll_transmit() {
+        let mut mailboxes = Mailboxes::<1>::new();
+        mailboxes.busy = 2;
+        let queued = frame(1, 0);
+
+        let mut future = mailboxes.transmit(&queued);
+        let mut future = Pin::new(&mut future);
+        assert!(poll_once(future.as_mut()).is_pending());
+        assert!(poll_once(future.as_mut()).is_pending());
+        assert_eq!(poll_once(future.as_mut()), Poll::Ready(Ok(None)));
+        assert_eq!(mailboxes.send(), Some(queued));
+    }
+
+    #[test]
+    fn poll_receive() {
+        let mut frames = Frames::new([frame(1, 0)]);
+        frames.busy = 1;
+
+        let mut future = frames.receive();
+        let mut future = Pin::new(&mut future);
+        assert!(poll_once(future.as_mut()).is_pending());
+        assert_eq!(poll_once(future.as_mut()), Poll::Ready(Ok(frame(1, 0))));
+    }
+
+    #[test]
+    fn poll_receive_event() {
+        let mut frames = Frames::new([frame(1, 0)]);
+        frames.busy = 1;
+
+        let mut future = frames.receive_event();
+        let mut future = Pin::new(&mut future);
+        assert!(poll_once(future.as_mut()).is_pending());
+        assert_eq!(
+            poll_once(future.as_mut()),
+            Poll::Ready(Ok(BusEvent::Frame(frame(1, 0))))
+        );
+
+        let mut future = frames.receive_event();
+        assert_eq!(
+            poll_once(Pin::new(&mut future)),
+            Poll::Ready(Err(ErrorKind::Other))
+        );
+    }
+}
@@ -0,0 +1,308 @@
+//! Concrete CAN identifiers
+
+use core::cmp::Ordering;
+use core::convert::TryFrom;
+
+use crate::Id;
+
+/// Standard 11-bit CAN Identifier (`0..=0x7FF`).
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct StandardId(u16);
+
+impl StandardId {
+    /// CAN ID `0`, the highest priority.
+    pub const ZERO: Self = StandardId(0);
+
+    /// CAN ID `0x7FF`, the lowest priority.
+    pub const MAX: Self = StandardId(0x7FF);
+
+    /// Tries to create a `StandardId` from a raw 16-bit integer.
+    ///
+    /// This will return `None` if `raw` is out of range of an 11-bit integer (`> 0x7FF`).
+    #[inline]
+    pub const fn new(raw: u16) -> Option<Self> {
+        if raw <= 0x7FF {
+            Some(StandardId(raw))
+        } else {
+            None
+        }
+    }
+
+    /// Creates a new `StandardId` without checking if it is inside the valid range.
+    ///
+    /// # Safety
+    /// Using this method can create an invalid ID and is thus marked as unsafe.
+    #[inline]
+    pub const unsafe fn new_unchecked(raw: u16) -> Self {
+        StandardId(raw)
+    }
+
+    /// Returns this CAN Identifier as a raw 16-bit integer.
+    #[inline]
+    pub const fn as_raw(&self) -> u16 {
+        self.0
+    }
+}
+
+/// Extended 29-bit CAN Identifier (`0..=0x1FFF_FFFF`).
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct ExtendedId(u32);
+
+impl ExtendedId {
+    /// CAN ID `0`, the highest priority.
+    pub const ZERO: Self = ExtendedId(0);
+
+    /// CAN ID `0x1FFFFFFF`, the lowest priority.
+    pub const MAX: Self = ExtendedId(0x1FFF_FFFF);
+
+    /// Tries to create a `ExtendedId` from a raw 32-bit integer.
+    ///
+    /// This will return `None` if `raw` is out of range of an 29-bit integer (`> 0x1FFF_FFFF`).
+    #[inline]
+    pub const fn new(raw: u32) -> Option<Self> {
+        if raw <= 0x1FFF_FFFF {
+            Some(ExtendedId(raw))
+        } else {
+            None
+        }
+    }
+
+    /// Creates a new `ExtendedId` without checking if it is inside the valid range.
+    ///
+    /// # Safety
+    /// Using this method can create an invalid ID and is thus marked as unsafe.
+    #[inline]
+    pub const unsafe fn new_unchecked(raw: u32) -> Self {
+        ExtendedId(raw)
+    }
+
+    /// Returns this CAN Identifier as a raw 32-bit integer.
+    #[inline]
+    pub const fn as_raw(&self) -> u32 {
+        self.0
+    }
+
+    /// Returns the Base ID part of this extended identifier (its 11 most significant bits).
+    pub const fn standard_id(&self) -> StandardId {
+        // ID-28 to ID-18
+        StandardId((self.0 >> 18) as u16)
+    }
+}
+
+/// Either a `StandardId` or an `ExtendedId`.
+///
+/// The ordering of `AnyId` follows the CAN arbitration priority: an id that compares
+/// less than another one would win arbitration against it on the bus.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum AnyId {
+    /// Standard 11-bit Identifier (`0..=0x7FF`).
+    Standard(StandardId),
+
+    /// Extended 29-bit Identifier (`0..=0x1FFF_FFFF`).
+    Extended(ExtendedId),
+}
+
+impl Ord for AnyId {
+    fn cmp(&self, other: &Self) -> Ordering {
+        ArbitrationField::new(*self, false).cmp(&ArbitrationField::new(*other, false))
+    }
+}
+
+impl PartialOrd for AnyId {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// The arbitration field of a frame, laid out in the order its bits are transmitted on the bus.
+///
+/// Comparing two `ArbitrationField`s models a real bus arbitration: the field that compares
+/// less than the other one wins, as a dominant bit (`0`) overwrites a recessive bit (`1`).
+/// In particular:
+///
+/// - a base frame wins against an extended frame sharing its 11 most significant bits,
+///   as the SRR and IDE bits of the latter are recessive.
+/// - a data frame wins against a remote frame with the same identifier, as the RTR bit of
+///   the latter is recessive.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct ArbitrationField(u32);
+
+impl ArbitrationField {
+    /// Creates the arbitration field of a data or remote frame with the given identifier.
+    pub const fn new(id: AnyId, remote: bool) -> Self {
+        // Bit 31..21: ID-28 to ID-18 (or the whole base id)
+        // Bit 20:     RTR for base frames, SRR (always recessive) for extended frames
+        // Bit 19:     IDE
+        // Bit 18..1:  ID-17 to ID-0
+        // Bit 0:      RTR for extended frames
+        let raw = match id {
+            AnyId::Standard(id) => ((id.as_raw() as u32) << 21) | ((remote as u32) << 20),
+            AnyId::Extended(id) => {
+                ((id.standard_id().as_raw() as u32) << 21)
+                    | (1 << 20)
+                    | (1 << 19)
+                    | ((id.as_raw() & 0x3FFFF) << 1)
+                    | remote as u32
+            }
+        };
+
+        ArbitrationField(raw)
+    }
+
+    /// Returns the arbitration field as a raw 32-bit integer.
+    #[inline]
+    pub const fn as_raw(&self) -> u32 {
+        self.0
+    }
+}
+
+impl From<StandardId> for AnyId {
+    #[inline]
+    fn from(id: StandardId) -> Self {
+        AnyId::Standard(id)
+    }
+}
+
+impl From<ExtendedId> for AnyId {
+    #[inline]
+    fn from(id: ExtendedId) -> Self {
+        AnyId::Extended(id)
+    }
+}
+
+impl TryFrom<AnyId> for StandardId {
+    type Error = ExtendedId;
+
+    /// Returns the `ExtendedId` back as error if `id` is not a standard id.
+    #[inline]
+    fn try_from(id: AnyId) -> Result<Self, Self::Error> {
+        match id {
+            AnyId::Standard(id) => Ok(id),
+            AnyId::Extended(id) => Err(id),
+        }
+    }
+}
+
+impl TryFrom<AnyId> for ExtendedId {
+    type Error = StandardId;
+
+    /// Returns the `StandardId` back as error if `id` is not an extended id.
+    #[inline]
+    fn try_from(id: AnyId) -> Result<Self, Self::Error> {
+        match id {
+            AnyId::Standard(id) => Err(id),
+            AnyId::Extended(id) => Ok(id),
+        }
+    }
+}
+
+impl Id for AnyId {
+    type BaseId = StandardId;
+    type ExtendedId = ExtendedId;
+
+    fn to_any_id(&self) -> AnyId {
+        *self
+    }
+
+    fn base_id(&self) -> Option<StandardId> {
+        match self {
+            AnyId::Standard(id) => Some(*id),
+            AnyId::Extended(_) => None,
+        }
+    }
+
+    fn extended_id(&self) -> Option<ExtendedId> {
+        match self {
+            AnyId::Standard(_) => None,
+            AnyId::Extended(id) => Some(*id),
+        }
+    }
+}
+
+impl Id for StandardId {
+    type BaseId = StandardId;
+    type ExtendedId = ExtendedId;
+
+    fn to_any_id(&self) -> AnyId {
+        AnyId::Standard(*self)
+    }
+
+    fn base_id(&self) -> Option<StandardId> {
+        Some(*self)
+    }
+
+    fn extended_id(&self) -> Option<ExtendedId> {
+        None
+    }
+}
+
+impl Id for ExtendedId {
+    type BaseId = StandardId;
+    type ExtendedId = ExtendedId;
+
+    fn to_any_id(&self) -> AnyId {
+        AnyId::Extended(*self)
+    }
+
+    fn base_id(&self) -> Option<StandardId> {
+        None
+    }
+
+    fn extended_id(&self) -> Option<ExtendedId> {
+        Some(*self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{extended, standard};
+
+    #[test]
+    fn ranges() {
+        assert_eq!(StandardId::new(0x7FF), Some(StandardId::MAX));
+        assert_eq!(StandardId::new(0x800), None);
+        assert_eq!(ExtendedId::new(0x1FFF_FFFF), Some(ExtendedId::MAX));
+        assert_eq!(ExtendedId::new(0x2000_0000), None);
+        assert_eq!(ExtendedId::MAX.standard_id(), StandardId::MAX);
+    }
+
+    #[test]
+    fn priority() {
+        assert!(standard(0x100) < standard(0x101));
+        assert!(extended(0x100) < extended(0x101));
+
+        // The base id is compared first.
+        assert!(extended(0x0FFF_FFFF) < standard(0x400));
+        assert!(standard(0x3FF) < extended(0x1000_0000));
+
+        // A base frame wins against an extended frame sharing its 11 most significant bits.
+        assert!(standard(0x123) < extended(0x123 << 18));
+        assert!(standard(0x123) < extended((0x123 << 18) | 0x3FFFF));
+    }
+
+    #[test]
+    fn remote_frames() {
+        let data = ArbitrationField::new(standard(0x123), false);
+        let remote = ArbitrationField::new(standard(0x123), true);
+        assert!(data < remote);
+        assert!(remote < ArbitrationField::new(standard(0x124), false));
+
+        // The SRR bit of an extended frame is recessive, like the RTR bit of a remote frame.
+        assert!(remote < ArbitrationField::new(extended(0x123 << 18), false));
+
+        let data = ArbitrationField::new(extended(0x1234), false);
+        let remote = ArbitrationField::new(extended(0x1234), true);
+        assert!(data < remote);
+        assert!(remote < ArbitrationField::new(extended(0x1235), false));
+    }
+
+    #[test]
+    fn conversions() {
+        let id = ExtendedId::new(0x1234).unwrap();
+        assert_eq!(ExtendedId::try_from(AnyId::from(id)), Ok(id));
+        assert_eq!(StandardId::try_from(AnyId::from(id)), Err(id));
+        assert_eq!(AnyId::from(id).extended_id(), Some(id));
+        assert_eq!(AnyId::from(id).base_id(), None);
+    }
+}
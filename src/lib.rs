@@ -1,9 +1,37 @@
 //! Controller Area Network
 #![no_std]
 
+use core::cmp::Ordering;
 use core::future::Future;
 
-use nb;
+use crate::filter::{FilterBankConfig, Fit, MaskFilter};
+
+pub mod dispatch;
+mod error;
+mod event;
+pub mod fd;
+pub mod filter;
+mod frame;
+pub mod future;
+mod id;
+mod mode;
+mod mutex;
+pub mod queue;
+pub mod shared;
+pub mod timestamp;
+pub mod timing;
+mod transmit;
+
+#[cfg(test)]
+mod test_utils;
+
+pub use crate::error::{Error, ErrorCounters, ErrorKind, ErrorState, RecoveryMode};
+pub use crate::event::{BusEvent, ErrorLocation};
+pub use crate::frame::{ClassicFrame, ClassicFrameBuilder, FrameError};
+pub use crate::id::{AnyId, ArbitrationField, ExtendedId, StandardId};
+pub use crate::mode::Mode;
+pub use crate::timestamp::{Stamped, Timestamp, TxEvent};
+pub use crate::transmit::{AbortOutcome, Tracked, TransmitOutcome};
 
 /// A type that can either be `BaseId` or `ExtendedId`
 pub trait Id {
@@ -20,6 +48,17 @@ pub trait Id {
     /// Returns `Some(extended_id)` if this Can-ID is 29-bit.
     /// Returns `None` if this Can-ID is 11-bit.
     fn extended_id(&self) -> Option<Self::ExtendedId>;
+
+    /// Returns this Can-ID as an `AnyId`.
+    fn to_any_id(&self) -> AnyId;
+
+    /// Compares the bus priority of this Can-ID with another one.
+    ///
+    /// Returns `Ordering::Less` if a data frame with this Can-ID would win arbitration against
+    /// a data frame with the `other` Can-ID. See `ArbitrationField` for the exact rules.
+    fn priority_cmp<I: Id>(&self, other: &I) -> Ordering {
+        self.to_any_id().cmp(&other.to_any_id())
+    }
 }
 
 /// A type that will either accept or filter a `Frame`.
@@ -46,6 +85,45 @@ pub trait Filter {
     /// ### Panic
     /// (for implementers: must) panic if mask have bits equal to `1` for bit_position `>= 30`.
     fn from_mask(mask: u32, filter: u32) -> Self;
+
+    /// Constructs a filter accepting `Frame`s with identifiers from `first` to `last` (inclusive).
+    ///
+    /// By default, this uses `from_mask` with the smallest aligned block of a power of two
+    /// identifiers containing the range (see `filter::MaskFilter::from_range`), which is only
+    /// exact if the range is such a block. Implementers supporting range filters should
+    /// override this method.
+    ///
+    /// ### Panic
+    /// Panics if `first` and `last` are not of the same kind, or if `first` is above `last`.
+    fn from_range(first: Self::Id, last: Self::Id) -> Fit<Self>
+    where
+        Self: Sized,
+    {
+        MaskFilter::from_range(first.to_any_id(), last.to_any_id()).map(|mask| mask.to_filter())
+    }
+
+    /// Constructs a filter accepting `Frame`s with any of the identifiers in `ids`.
+    ///
+    /// By default, this uses `from_mask` with the most specific mask accepting all `ids`
+    /// (see `filter::MaskFilter::from_list`), which may accept other identifiers as well.
+    /// Implementers supporting list filters should override this method.
+    ///
+    /// ### Panic
+    /// Panics if `ids` is empty.
+    fn from_list(ids: &[Self::Id]) -> Fit<Self>
+    where
+        Self: Sized,
+    {
+        MaskFilter::from_list(ids).map(|mask| mask.to_filter())
+    }
+
+    /// Returns true if `Frame`s with the identifier `id` are accepted by this filter.
+    fn matches_id(&self, id: &Self::Id) -> bool;
+
+    /// Returns true if `frame` is accepted by this filter.
+    fn matches<F: Frame<Id = Self::Id>>(&self, frame: &F) -> bool {
+        self.matches_id(&frame.id())
+    }
 }
 
 /// A Can Frame
@@ -53,6 +131,20 @@ pub trait Frame {
     /// The Id type of this Frame
     type Id: Id;
 
+    /// Creates a new data frame with the given identifier and payload.
+    ///
+    /// Returns `None` if `data` is longer than this frame type supports (8 bytes for classic Can).
+    fn new(id: impl Into<Self::Id>, data: &[u8]) -> Option<Self>
+    where
+        Self: Sized;
+
+    /// Creates a new remote frame with the given identifier, requesting `dlc` bytes.
+    ///
+    /// Returns `None` if `dlc` is out of range (larger than 8 for classic Can).
+    fn new_remote(id: impl Into<Self::Id>, dlc: usize) -> Option<Self>
+    where
+        Self: Sized;
+
     /// Returns true if this `Frame` is a remote frame
     fn is_remote_frame(&self) -> bool;
 
@@ -72,9 +164,26 @@ pub trait Frame {
     /// Returns the Can-ID
     fn id(&self) -> Self::Id;
 
+    /// Returns the data length code (DLC).
+    ///
+    /// For data frames this is the length of the payload, for remote frames the requested length.
+    fn dlc(&self) -> usize;
+
     /// Returns `Some(Data)` if data frame.
     /// Returns `None` if remote frame.
     fn data(&self) -> Option<&[u8]>;
+
+    /// Returns the arbitration field of this `Frame`, as transmitted on the bus.
+    fn arbitration_field(&self) -> ArbitrationField {
+        ArbitrationField::new(self.id().to_any_id(), self.is_remote_frame())
+    }
+
+    /// Compares the bus priority of this `Frame` with another one.
+    ///
+    /// Returns `Ordering::Less` if this `Frame` would win arbitration against `other`.
+    fn priority_cmp<F: Frame>(&self, other: &F) -> Ordering {
+        self.arbitration_field().cmp(&other.arbitration_field())
+    }
 }
 
 /// A Can-FD Frame
@@ -85,10 +194,32 @@ pub trait FdFrame {
     /// The Id type of this Frame
     type Id: Id;
 
+    /// Creates a new Can-FD data frame with the given identifier and payload.
+    ///
+    /// The payload is padded to the next valid Can-FD length (see `fd::padded_len`).
+    /// If `bit_rate_switch` is true, the data phase will be transmitted at the data bit rate.
+    ///
+    /// Returns `None` if `data` is longer than 64 bytes.
+    fn new(id: impl Into<Self::Id>, data: &[u8], bit_rate_switch: bool) -> Option<Self>
+    where
+        Self: Sized;
+
     /// Returns true if this frame would/has be(en) transmitted as a Can-Fd frame.
     /// Returns false if this frame would/has be(en) transmitted as a "ordinary" Can frame.
     fn is_fd_frame(&self) -> bool;
 
+    /// Returns true if the data phase of this frame would/has be(en) transmitted at the data
+    /// bit rate (the BRS bit is set).
+    ///
+    /// Always returns false for "ordinary" Can frames.
+    fn is_bit_rate_switched(&self) -> bool;
+
+    /// Returns true if the transmitter of this frame was error passive (the ESI bit is set).
+    ///
+    /// The ESI bit is set by the transmitting controller, it can not be requested.
+    /// Always returns false for "ordinary" Can frames.
+    fn is_error_passive(&self) -> bool;
+
     /// Returns true if this `Frame` is a remote frame
     fn is_remote_frame(&self) -> bool;
 
@@ -111,6 +242,29 @@ pub trait FdFrame {
     /// Returns `Some(Data)` if data frame.
     /// Returns `None` if remote frame.
     fn data(&self) -> Option<&[u8]>;
+
+    /// Returns the arbitration field of this `FdFrame`, as transmitted on the bus.
+    ///
+    /// The RRS bit replacing RTR in Can-FD frames is always dominant, Can-FD frames thus
+    /// compare as data frames.
+    fn arbitration_field(&self) -> ArbitrationField {
+        ArbitrationField::new(self.id().to_any_id(), self.is_remote_frame())
+    }
+
+    /// Compares the bus priority of this `FdFrame` with another one.
+    ///
+    /// Returns `Ordering::Less` if this `FdFrame` would win arbitration against `other`.
+    fn priority_cmp<F: FdFrame>(&self, other: &F) -> Ordering {
+        self.arbitration_field().cmp(&other.arbitration_field())
+    }
+}
+
+/// A frame carrying the time it was received or transmitted at.
+///
+/// Implemented by the `Frame` types of controllers capturing timestamps.
+pub trait Timestamped {
+    /// Returns the time the frame was received or transmitted at.
+    fn timestamp(&self) -> Timestamp;
 }
 
 /// A CAN interface
@@ -124,7 +278,7 @@ pub trait Interface {
     type Frame: Frame<Id = Self::Id>;
 
     /// The Interface Error type
-    type Error;
+    type Error: Error;
 
     /// The Filter type used in this `Interface`
     type Filter: Filter<Id = Self::Id>;
@@ -132,56 +286,357 @@ pub trait Interface {
 
 /// A CAN interface that is able to transmit frames.
 pub trait Transmitter: Interface {
+    /// The future returned by `transmit`.
+    type TransmitterFuture<'a>: Future<Output = Result<Option<Self::Frame>, Self::Error>> + 'a
+    where
+        Self: 'a;
+
     /// Put a `Frame` in the transmit buffer (or a free mailbox).
     ///
     /// If the buffer is full, this function will try to replace a lower priority `Frame`
     /// and return it. This is to avoid the priority inversion problem.
-    fn transmit(&mut self, frame: &Self::Frame) -> nb::Result<Option<Self::Frame>, Self::Error>;
+    fn try_transmit(&mut self, frame: &Self::Frame)
+        -> nb::Result<Option<Self::Frame>, Self::Error>;
+
+    /// Put a `Frame` in the transmit buffer (or a free mailbox), waiting for space if needed.
+    ///
+    /// The future resolves once the `Frame` is queued, with the replaced lower priority `Frame`
+    /// if any, as `try_transmit` does.
+    ///
+    /// Implementations without a transmit interrupt may use `future::PollTransmit`.
+    fn transmit<'a>(&'a mut self, frame: &'a Self::Frame) -> Self::TransmitterFuture<'a>;
+}
+
+/// A CAN interface able to abort frames pending for transmission.
+pub trait Abort: Transmitter {
+    /// Abort the pending frames with the identifier `id`.
+    ///
+    /// Returns `AbortOutcome::Aborted` if any frame was aborted. A frame whose transmission is
+    /// ongoing may not be aborted anymore, this method then returns immediately with
+    /// `AbortOutcome::InProgress`. Use `AbortTracked` to wait for the outcome of such a frame.
+    fn abort(&mut self, id: &Self::Id) -> Result<AbortOutcome, Self::Error>;
+
+    /// Abort all pending frames.
+    ///
+    /// `report` is called with the identifier of every frame that was pending, along with
+    /// whether it was actually aborted, already sent or still being transmitted.
+    fn abort_all<R>(&mut self, report: R) -> Result<(), Self::Error>
+    where
+        R: FnMut(Self::Id, AbortOutcome);
+}
+
+/// A CAN interface identifying the frames put in its transmit buffer by handles.
+pub trait TransmitHandles: Transmitter {
+    /// A handle identifying a frame put in the transmit buffer.
+    type Handle: Copy;
+}
+
+/// A CAN interface able to tell when a transmitted frame actually went out on the bus.
+pub trait TrackedTransmitter: TransmitHandles {
+    /// The future returned by `wait_sent`.
+    type SentFuture<'a>: Future<Output = Result<TransmitOutcome, Self::Error>> + 'a
+    where
+        Self: 'a;
+
+    /// Put a `Frame` in the transmit buffer (or a free mailbox), as `Transmitter::try_transmit`
+    /// does, and return a handle to it along with the replaced lower priority `Frame`, if any.
+    fn try_transmit_tracked(
+        &mut self,
+        frame: &Self::Frame,
+    ) -> nb::Result<Tracked<Self::Handle, Self::Frame>, Self::Error>;
+
+    /// Wait until the frame identified by `handle` has left the transmit buffer.
+    ///
+    /// Resolves with an error if the transmission failed for another reason than a lost
+    /// arbitration, e.g. with an error of kind `ErrorKind::Acknowledge` or `ErrorKind::BusOff`.
+    /// The result for a `handle` whose outcome was already reported is unspecified.
+    fn wait_sent(&mut self, handle: Self::Handle) -> Self::SentFuture<'_>;
+}
+
+/// A CAN interface able to abort tracked frames by their handle.
+pub trait AbortTracked: TrackedTransmitter + Abort {
+    /// Abort the pending frame identified by `handle`.
+    ///
+    /// If the transmission of the frame is ongoing, this returns `AbortOutcome::InProgress`
+    /// immediately, and `TrackedTransmitter::wait_sent` with the same `handle` resolves with
+    /// its outcome.
+    fn abort_tracked(&mut self, handle: Self::Handle) -> Result<AbortOutcome, Self::Error>;
+}
+
+/// A CAN interface able to transmit frames at most once, without automatic retransmission.
+///
+/// Useful for time-triggered schedules, where a frame must not be sent late.
+pub trait OneShot: Transmitter {
+    /// The future returned by `transmit_once`.
+    type OneShotFuture<'a>: Future<Output = Result<TransmitOutcome, Self::Error>> + 'a
+    where
+        Self: 'a;
+
+    /// Enable or disable the automatic retransmission of frames that lost arbitration or were
+    /// not acknowledged, for all frames transmitted afterwards.
+    ///
+    /// Returns an error of kind `ErrorKind::Unsupported` if retransmission can not be disabled.
+    fn set_automatic_retransmission(&mut self, enabled: bool) -> Result<(), Self::Error>;
+
+    /// Transmit a `Frame` a single time, regardless of the automatic retransmission setting,
+    /// and wait for the outcome of the attempt.
+    ///
+    /// Resolves with `TransmitOutcome::ArbitrationLost` if the frame lost arbitration, and with
+    /// an error if the attempt failed for another reason, e.g. with an error of kind
+    /// `ErrorKind::Acknowledge`.
+    fn transmit_once<'a>(&'a mut self, frame: &'a Self::Frame) -> Self::OneShotFuture<'a>;
+}
+
+/// A CAN interface reporting the transmission time of the frames it sent.
+///
+/// The reports of frames put in the transmit buffer with a handle (e.g. by
+/// `TrackedTransmitter::try_transmit_tracked`) carry it, so they can be matched with the frames
+/// even if several frames with the same identifier were transmitted.
+pub trait TxEventReceiver: TransmitHandles {
+    /// The future returned by `receive_tx_event`.
+    type TxEventFuture<'a>: Future<Output = Result<TxEvent<Self::Id, Self::Handle>, Self::Error>>
+        + 'a
+    where
+        Self: 'a;
+
+    /// Return the oldest report of a transmitted frame, waiting for one if needed.
+    fn receive_tx_event(&mut self) -> Self::TxEventFuture<'_>;
 }
 
 /// A CAN interface that is able to receive frames.
 pub trait Receiver: Interface {
-    /// Return the available `Frame` with the highest priority (lowest ID).
-    ///
-    /// NOTE: Can-FD Frames will not be received using this function.
+    /// The future returned by `receive`.
     type ReceiverFuture<'a>: Future<Output = Result<Self::Frame, Self::Error>> + 'a
     where
         Self: 'a;
 
-    /// TODO
-    fn receive<'a>(&'a mut self) -> Self::ReceiverFuture<'a>;
+    /// Return the available `Frame` with the highest priority (lowest ID), if any.
+    ///
+    /// Returns `WouldBlock` if no `Frame` has been received.
+    ///
+    /// NOTE: Can-FD Frames will not be received using this function.
+    fn try_receive(&mut self) -> nb::Result<Self::Frame, Self::Error>;
+
+    /// Return the available `Frame` with the highest priority (lowest ID), waiting for one if needed.
+    ///
+    /// Implementations without a receive interrupt may use `future::PollReceive`.
+    ///
+    /// NOTE: Can-FD Frames will not be received using this function.
+    fn receive(&mut self) -> Self::ReceiverFuture<'_>;
 
     /// Set the can controller in a mode where it only accept frames matching the given filter.
     ///
     /// If there exists several receive buffers, this filter will be applied for all of them.
     ///
     /// *Note: Even after this method has been called, there may still be `Frame`s in the receive buffer with
-    /// identifiers that would not been received with this `Filter`.*
+    /// identifiers that would not been received with this `Filter`. Use `Filter::matches` to
+    /// discard them.*
     fn set_filter(&mut self, filter: Self::Filter);
 
     /// Set the can controller in a mode where it will accept all frames.
     fn clear_filter(&mut self);
 }
 
-///// A CAN interface also supporting Can-FD
-/////
-///// May be a `FdTransmitter`, `FdReceiver` or both.
-//pub trait FdInterface: Interface {
-//    /// The Can Frame this Interface operates on
-//    type FdFrame: FdFrame;
-//}
-//
-///// A CAN-FD interface that is able to transmit frames.
-//pub trait FdTransmitter: FdInterface + Receiver {
-//    /// Put a `FdFrame` in the transmit buffer (or a free mailbox).
-//    ///
-//    /// If the buffer is full, this function will try to replace a lower priority `FdFrame`
-//    /// and return it. This is to avoid the priority inversion problem.
-//    fn transmit(&mut self, frame: &Self::FdFrame) -> nb::Result<Option<Self::FdFrame>, Self::Error>;
-//}
-//
-///// A CAN-FD interface that is able to receive frames.
-//pub trait FdReceiver: FdInterface + Transmitter {
-//    /// Read the available `FdFrame` with the highest priority (lowest ID).
-//    fn receive(&mut self) -> nb::Result<Self::FdFrame, Self::Error>;
-//}
+/// A CAN interface reporting error frames, overload frames and state changes along with the
+/// received frames.
+pub trait EventReceiver: Receiver {
+    /// The future returned by `receive_event`.
+    type EventFuture<'a>: Future<Output = Result<BusEvent<Self::Frame>, Self::Error>> + 'a
+    where
+        Self: 'a;
+
+    /// Return the oldest `BusEvent`, if any.
+    ///
+    /// Received frames are returned as `BusEvent::Frame`, in the order `try_receive` would
+    /// return them.
+    ///
+    /// Returns `WouldBlock` if no event has occurred.
+    fn try_receive_event(&mut self) -> nb::Result<BusEvent<Self::Frame>, Self::Error>;
+
+    /// Return the oldest `BusEvent`, waiting for one if needed.
+    ///
+    /// Implementations without a receive interrupt may use `future::PollReceiveEvent`.
+    fn receive_event(&mut self) -> Self::EventFuture<'_>;
+}
+
+/// A CAN interface that can be split into independent transmit and receive halves.
+///
+/// This allows receiving in one task while transmitting in another.
+pub trait Split: Interface {
+    /// The transmitting half.
+    type Tx: Transmitter<
+        Id = Self::Id,
+        Frame = Self::Frame,
+        Error = Self::Error,
+        Filter = Self::Filter,
+    >;
+
+    /// The receiving half.
+    type Rx: Receiver<
+        Id = Self::Id,
+        Frame = Self::Frame,
+        Error = Self::Error,
+        Filter = Self::Filter,
+    >;
+
+    /// Split the interface into its transmitting and receiving halves.
+    fn split(self) -> (Self::Tx, Self::Rx);
+}
+
+/// A CAN interface able to report its fault confinement state.
+pub trait BusStatus: Interface {
+    /// The future returned by `wait_for_state_change`.
+    type StateChangeFuture<'a>: Future<Output = Result<ErrorState, Self::Error>> + 'a
+    where
+        Self: 'a;
+
+    /// Returns the current fault confinement state.
+    fn error_state(&self) -> ErrorState;
+
+    /// Returns the current transmit and receive error counters.
+    fn error_counters(&self) -> ErrorCounters;
+
+    /// Wait until the fault confinement state changes, and return the new state.
+    fn wait_for_state_change(&mut self) -> Self::StateChangeFuture<'_>;
+}
+
+/// A CAN interface whose bus-off recovery can be controlled.
+///
+/// To recover from bus-off, a node must monitor 128 occurrences of 11 consecutive recessive
+/// bits before it becomes error active again.
+pub trait BusOffRecovery: Interface {
+    /// The future returned by `wait_for_recovery`.
+    type RecoveryFuture<'a>: Future<Output = Result<(), Self::Error>> + 'a
+    where
+        Self: 'a;
+
+    /// Returns how the node currently leaves the bus-off state.
+    fn recovery_mode(&self) -> RecoveryMode;
+
+    /// Set how the node leaves the bus-off state.
+    fn set_recovery_mode(&mut self, mode: RecoveryMode) -> Result<(), Self::Error>;
+
+    /// Start the recovery sequence.
+    ///
+    /// Does nothing if the node is not bus-off, or is already recovering.
+    fn start_recovery(&mut self) -> Result<(), Self::Error>;
+
+    /// Wait until the recovery sequence has finished and the node is error active again.
+    ///
+    /// Resolves immediately if the node is not bus-off. In `RecoveryMode::Manual`, this will
+    /// only resolve after `start_recovery` has been called.
+    fn wait_for_recovery(&mut self) -> Self::RecoveryFuture<'_>;
+}
+
+/// A CAN interface with several filter banks, each routing the frames it accepts to a receive FIFO.
+///
+/// A frame is received if it is accepted by any enabled filter bank.
+/// `Receiver::set_filter` and `Receiver::clear_filter` reconfigure all filter banks at once.
+pub trait FilterBanks: Receiver {
+    /// Returns the number of filter banks offered by the hardware.
+    fn filter_bank_count(&self) -> usize;
+
+    /// Returns the number of receive FIFOs filter banks can be assigned to.
+    fn fifo_count(&self) -> usize;
+
+    /// Configure and enable the filter bank at `index`.
+    ///
+    /// Returns an error of kind `ErrorKind::FilterBanksExhausted` if `index` is not below
+    /// `filter_bank_count`, or if the identifiers of a `FilterBankMode::List` do not fit
+    /// in a single bank.
+    fn set_filter_bank(
+        &mut self,
+        index: usize,
+        config: FilterBankConfig<'_, Self::Filter, Self::Id>,
+    ) -> Result<(), Self::Error>;
+
+    /// Disable the filter bank at `index`, so that it does not accept any frame.
+    fn disable_filter_bank(&mut self, index: usize) -> Result<(), Self::Error>;
+
+    /// Configure the filter banks in order, starting at index 0, and disable the remaining ones.
+    ///
+    /// Returns an error of kind `ErrorKind::FilterBanksExhausted` if there are more `configs`
+    /// than filter banks.
+    fn set_filter_banks<'a, C>(&mut self, configs: C) -> Result<(), Self::Error>
+    where
+        C: IntoIterator<Item = FilterBankConfig<'a, Self::Filter, Self::Id>>,
+        Self::Id: 'a,
+    {
+        let mut index = 0;
+        for config in configs {
+            self.set_filter_bank(index, config)?;
+            index += 1;
+        }
+        for index in index..self.filter_bank_count() {
+            self.disable_filter_bank(index)?;
+        }
+        Ok(())
+    }
+}
+
+/// A CAN interface whose operating mode can be changed.
+pub trait ModeControl: Interface {
+    /// Returns the current operating mode.
+    fn mode(&self) -> Mode;
+
+    /// Set the operating mode.
+    ///
+    /// Returns an error of kind `ErrorKind::Unsupported` if the controller does not support `mode`,
+    /// in which case the current mode is kept.
+    fn set_mode(&mut self, mode: Mode) -> Result<(), Self::Error>;
+}
+
+/// A CAN interface also supporting Can-FD
+///
+/// May be a `FdTransmitter`, `FdReceiver` or both.
+#[cfg(feature = "unproven")]
+pub trait FdInterface: Interface {
+    /// The Can-FD Frame this Interface operates on
+    type FdFrame: FdFrame<Id = Self::Id>;
+}
+
+/// A CAN-FD interface that is able to transmit frames.
+#[cfg(feature = "unproven")]
+pub trait FdTransmitter: FdInterface + Transmitter {
+    /// The future returned by `transmit_fd`.
+    type FdTransmitterFuture<'a>: Future<Output = Result<Option<Self::FdFrame>, Self::Error>> + 'a
+    where
+        Self: 'a;
+
+    /// Put a `FdFrame` in the transmit buffer (or a free mailbox).
+    ///
+    /// If the buffer is full, this function will try to replace a lower priority `FdFrame`
+    /// and return it. This is to avoid the priority inversion problem.
+    fn try_transmit_fd(
+        &mut self,
+        frame: &Self::FdFrame,
+    ) -> nb::Result<Option<Self::FdFrame>, Self::Error>;
+
+    /// Put a `FdFrame` in the transmit buffer (or a free mailbox), waiting for space if needed.
+    ///
+    /// The future resolves once the `FdFrame` is queued, with the replaced lower priority
+    /// `FdFrame` if any, as `try_transmit_fd` does.
+    fn transmit_fd<'a>(&'a mut self, frame: &'a Self::FdFrame) -> Self::FdTransmitterFuture<'a>;
+}
+
+/// A CAN-FD interface that is able to receive frames.
+#[cfg(feature = "unproven")]
+pub trait FdReceiver: FdInterface + Receiver {
+    /// The future returned by `receive_fd`.
+    type FdReceiverFuture<'a>: Future<Output = Result<Self::FdFrame, Self::Error>> + 'a
+    where
+        Self: 'a;
+
+    /// Return the available `FdFrame` with the highest priority (lowest ID), if any.
+    ///
+    /// Unlike `Receiver::try_receive`, this also receives "ordinary" Can frames, represented as
+    /// `FdFrame`s for which `is_fd_frame` returns false.
+    fn try_receive_fd(&mut self) -> nb::Result<Self::FdFrame, Self::Error>;
+
+    /// Return the available `FdFrame` with the highest priority (lowest ID), waiting for one
+    /// if needed.
+    ///
+    /// Unlike `Receiver::receive`, this also receives "ordinary" Can frames, represented as
+    /// `FdFrame`s for which `is_fd_frame` returns false.
+    fn receive_fd(&mut self) -> Self::FdReceiverFuture<'_>;
+}
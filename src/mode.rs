@@ -0,0 +1,29 @@
+//! Can controller operating modes
+
+/// The operating mode of a Can controller.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum Mode {
+    /// The controller takes part in bus activities as usual.
+    Normal,
+
+    /// Transmitted frames are looped back internally and received by the controller itself.
+    /// The controller neither drives nor samples the bus.
+    InternalLoopback,
+
+    /// Transmitted frames are sent on the bus, and also received by the controller itself.
+    /// The controller does not require an acknowledgement from another node.
+    ExternalLoopback,
+
+    /// The controller receives frames but only sends recessive bits: it neither acknowledges
+    /// frames nor signals errors, and can not transmit. Also known as listen-only or silent mode.
+    BusMonitoring,
+
+    /// Combination of `InternalLoopback` and `BusMonitoring`: transmitted frames are looped back
+    /// internally while the bus is left untouched.
+    SilentLoopback,
+
+    /// The controller receives and acknowledges frames, but does not transmit, nor send error
+    /// or overload frames.
+    RestrictedOperation,
+}
@@ -0,0 +1,38 @@
+//! A minimal mutex for state shared between tasks
+//!
+//! Without the `critical-section` feature, the mutex is a plain `RefCell`, so the state can
+//! only be shared between tasks of a single executor. With it, every access happens within a
+//! critical section, and the mutex can be shared between executors and interrupt handlers.
+
+use core::cell::RefCell;
+
+pub(crate) struct Mutex<T> {
+    inner: RefCell<T>,
+}
+
+impl<T> Mutex<T> {
+    pub(crate) const fn new(value: T) -> Self {
+        Mutex {
+            inner: RefCell::new(value),
+        }
+    }
+
+    /// Runs `f` with exclusive access to the protected value.
+    ///
+    /// `f` must not lock the same mutex again.
+    pub(crate) fn lock<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        #[cfg(feature = "critical-section")]
+        return critical_section::with(|_| f(&mut self.inner.borrow_mut()));
+
+        #[cfg(not(feature = "critical-section"))]
+        f(&mut self.inner.borrow_mut())
+    }
+
+    pub(crate) fn into_inner(self) -> T {
+        self.inner.into_inner()
+    }
+}
+
+// Safety: all accesses to the `RefCell` happen within a critical section.
+#[cfg(feature = "critical-section")]
+unsafe impl<T: Send> Sync for Mutex<T> {}
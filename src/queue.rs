@@ -0,0 +1,409 @@
+//! Software priority transmit queue
+
+use core::future::{poll_fn, Future};
+use core::task::Poll;
+
+use crate::future::PollTransmit;
+use crate::{Abort, AbortOutcome, Frame, Id, Interface, Transmitter};
+
+/// A `Transmitter` queueing up to `N` frames in software in front of another `Transmitter`.
+///
+/// Queued frames are moved into the wrapped transmitter highest priority first, and frames
+/// replaced by the wrapped transmitter are put back in the queue instead of being lost.
+/// Frames of equal priority are transmitted in the order they were queued.
+///
+/// Frames only move on when the queue is used, or when `refill` is called. Call `refill` when
+/// a mailbox becomes free (e.g. from the transmit interrupt), or use `flush`.
+pub struct TxQueue<T: Transmitter, const N: usize> {
+    transmitter: T,
+
+    /// The queued frames, sorted by decreasing priority.
+    frames: [Option<T::Frame>; N],
+    len: usize,
+    high_water_mark: usize,
+}
+
+impl<T, const N: usize> TxQueue<T, N>
+where
+    T: Transmitter,
+    T::Frame: Clone,
+{
+    /// Creates an empty `TxQueue` in front of `transmitter`.
+    ///
+    /// ### Panic
+    /// Panics if `N` is 0.
+    pub fn new(transmitter: T) -> Self {
+        assert!(N > 0, "queue capacity must not be 0");
+        TxQueue {
+            transmitter,
+            frames: core::array::from_fn(|_| None),
+            len: 0,
+            high_water_mark: 0,
+        }
+    }
+
+    /// Returns the wrapped transmitter, dropping the queued frames.
+    pub fn into_inner(self) -> T {
+        self.transmitter
+    }
+
+    /// Returns the number of queued frames.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if no frame is queued.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the largest number of frames queued at once.
+    pub fn high_water_mark(&self) -> usize {
+        self.high_water_mark
+    }
+
+    /// Resets the high-water mark to the current number of queued frames.
+    pub fn reset_high_water_mark(&mut self) {
+        self.high_water_mark = self.len;
+    }
+
+    /// Move queued frames into the wrapped transmitter, highest priority first, until it is full.
+    pub fn refill(&mut self) -> Result<(), T::Error> {
+        while self.len > 0 {
+            let frame = self.frames[0].as_ref().expect("queued frame");
+            match self.transmitter.try_transmit(frame) {
+                Ok(replaced) => {
+                    self.remove(0);
+                    // The replaced frame has a lower priority, so the queue has room for it.
+                    if let Some(replaced) = replaced {
+                        self.insert(replaced);
+                    }
+                }
+                Err(nb::Error::WouldBlock) => return Ok(()),
+                Err(nb::Error::Other(e)) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Wait until all queued frames have been moved into the wrapped transmitter.
+    ///
+    /// The returned future calls `refill` each time it is polled, and wakes itself up
+    /// immediately as long as frames are queued.
+    pub fn flush(&mut self) -> impl Future<Output = Result<(), T::Error>> + '_ {
+        poll_fn(move |cx| match self.refill() {
+            Err(e) => Poll::Ready(Err(e)),
+            Ok(()) if self.len == 0 => Poll::Ready(Ok(())),
+            Ok(()) => {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        })
+    }
+
+    /// Inserts `frame` after all queued frames of the same or higher priority.
+    ///
+    /// The queue must not be full.
+    fn insert(&mut self, frame: T::Frame) {
+        let field = frame.arbitration_field();
+        let index = self.frames[..self.len]
+            .iter()
+            .position(|queued| {
+                let queued = queued.as_ref().expect("queued frame");
+                queued.arbitration_field() > field
+            })
+            .unwrap_or(self.len);
+
+        self.frames[index..=self.len].rotate_right(1);
+        self.frames[index] = Some(frame);
+        self.len += 1;
+        self.high_water_mark = self.high_water_mark.max(self.len);
+    }
+
+    fn remove(&mut self, index: usize) -> T::Frame {
+        let frame = self.frames[index].take().expect("queued frame");
+        self.frames[index..self.len].rotate_left(1);
+        self.len -= 1;
+        frame
+    }
+}
+
+impl<T, const N: usize> Interface for TxQueue<T, N>
+where
+    T: Transmitter,
+    T::Frame: Clone,
+{
+    type Id = T::Id;
+    type Frame = T::Frame;
+    type Error = T::Error;
+    type Filter = T::Filter;
+}
+
+impl<T, const N: usize> Transmitter for TxQueue<T, N>
+where
+    T: Transmitter,
+    T::Frame: Clone,
+{
+    type TransmitterFuture<'a>
+        = PollTransmit<'a, Self>
+    where
+        Self: 'a;
+
+    /// Queue a `Frame`, and move queued frames into the wrapped transmitter.
+    ///
+    /// If the queue is full, this replaces the lowest priority queued `Frame` and returns it,
+    /// or returns `WouldBlock` if `frame` does not have a higher priority than that `Frame`.
+    ///
+    /// An error is only returned if `frame` was not queued. Errors of the wrapped transmitter
+    /// occurring afterwards are returned by the next call to `refill` or `flush`.
+    fn try_transmit(
+        &mut self,
+        frame: &Self::Frame,
+    ) -> nb::Result<Option<Self::Frame>, Self::Error> {
+        self.refill()?;
+
+        let mut replaced = None;
+        if self.len == N {
+            let lowest = self.frames[N - 1].as_ref().expect("queued frame");
+            if frame.priority_cmp(lowest).is_ge() {
+                return Err(nb::Error::WouldBlock);
+            }
+            replaced = Some(self.remove(N - 1));
+        }
+
+        self.insert(frame.clone());
+        // The frame is queued, a failure to move it on is reported by the next `refill`.
+        let _ = self.refill();
+        Ok(replaced)
+    }
+
+    fn transmit<'a>(&'a mut self, frame: &'a Self::Frame) -> Self::TransmitterFuture<'a> {
+        PollTransmit::new(self, frame)
+    }
+}
+
+impl<T, const N: usize> Abort for TxQueue<T, N>
+where
+    T: Abort,
+    T::Frame: Clone,
+{
+    /// Abort the queued and pending frames with the identifier `id`.
+    ///
+    /// The queued frames with the identifier `id` are always removed. If the wrapped
+    /// transmitter had a frame with this identifier, the outcome of aborting it is returned,
+    /// e.g. `AbortOutcome::InProgress` if it may still be transmitted. Otherwise, this returns
+    /// `AbortOutcome::Aborted` if a queued frame was removed.
+    ///
+    /// If the wrapped transmitter fails to abort its pending frames, the queued frames are
+    /// left untouched.
+    fn abort(&mut self, id: &Self::Id) -> Result<AbortOutcome, Self::Error> {
+        let outcome = self.transmitter.abort(id)?;
+
+        let raw_id = id.to_any_id();
+        let mut aborted = false;
+        let mut index = 0;
+        while index < self.len {
+            let queued = self.frames[index].as_ref().expect("queued frame");
+            if queued.id().to_any_id() == raw_id {
+                self.remove(index);
+                aborted = true;
+            } else {
+                index += 1;
+            }
+        }
+
+        Ok(match outcome {
+            AbortOutcome::NotPending if aborted => AbortOutcome::Aborted,
+            outcome => outcome,
+        })
+    }
+
+    /// Abort all queued and pending frames.
+    ///
+    /// If the wrapped transmitter fails to abort its pending frames, the queued frames are
+    /// left untouched.
+    fn abort_all<R>(&mut self, mut report: R) -> Result<(), Self::Error>
+    where
+        R: FnMut(Self::Id, AbortOutcome),
+    {
+        self.transmitter.abort_all(&mut report)?;
+        while self.len > 0 {
+            let frame = self.remove(0);
+            report(frame.id(), AbortOutcome::Aborted);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{frame, Mailboxes};
+    use crate::ErrorKind;
+
+    /// Sends all frames of `queue`, writing their identifiers and payloads to `sent` in bus
+    /// order. Returns the written part of `sent`.
+    fn drain<'a, const N: usize>(
+        queue: &mut TxQueue<Mailboxes<1>, N>,
+        sent: &'a mut [(u32, u8)],
+    ) -> &'a [(u32, u8)] {
+        let mut len = 0;
+        while let Some(frame) = queue.transmitter.send() {
+            sent[len] = (frame.arbitration_field().as_raw(), frame.data().unwrap()[0]);
+            len += 1;
+            queue.refill().unwrap();
+        }
+        &sent[..len]
+    }
+
+    fn raw(id: u16) -> u32 {
+        frame(id, 0).arbitration_field().as_raw()
+    }
+
+    #[test]
+    fn priority_order() {
+        let mut queue = TxQueue::<_, 4>::new(Mailboxes::<1>::new());
+        for id in [5, 3, 7, 1] {
+            assert_eq!(queue.try_transmit(&frame(id, 0)), Ok(None));
+        }
+        assert_eq!(queue.len(), 3);
+
+        let expected = [1, 3, 5, 7].map(|id| (raw(id), 0));
+        assert_eq!(drain(&mut queue, &mut [(0, 0); 4]), expected);
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn fifo_within_priority() {
+        let mut queue = TxQueue::<_, 4>::new(Mailboxes::<1>::new());
+        for data in 0..5 {
+            assert_eq!(queue.try_transmit(&frame(2, data)), Ok(None));
+        }
+
+        let expected = [0, 1, 2, 3, 4].map(|data| (raw(2), data));
+        assert_eq!(drain(&mut queue, &mut [(0, 0); 5]), expected);
+    }
+
+    #[test]
+    fn displaced_frames_are_requeued() {
+        let mut queue = TxQueue::<_, 2>::new(Mailboxes::<1>::new());
+        assert_eq!(queue.try_transmit(&frame(7, 0)), Ok(None));
+        assert!(queue.is_empty());
+
+        // The mailbox takes the higher priority frame, the displaced one goes back in the queue.
+        assert_eq!(queue.try_transmit(&frame(1, 0)), Ok(None));
+        assert_eq!(queue.len(), 1);
+
+        let expected = [(raw(1), 0), (raw(7), 0)];
+        assert_eq!(drain(&mut queue, &mut [(0, 0); 2]), expected);
+    }
+
+    #[test]
+    fn high_water_mark() {
+        let mut queue = TxQueue::<_, 4>::new(Mailboxes::<1>::new());
+        for id in [9, 8, 7, 6] {
+            queue.try_transmit(&frame(id, 0)).unwrap();
+        }
+        assert_eq!(queue.high_water_mark(), 3);
+
+        drain(&mut queue, &mut [(0, 0); 4]);
+        assert_eq!(queue.high_water_mark(), 3);
+        queue.reset_high_water_mark();
+        assert_eq!(queue.high_water_mark(), 0);
+    }
+
+    #[test]
+    fn full_queue() {
+        let mut queue = TxQueue::<_, 2>::new(Mailboxes::<1>::new());
+        for id in [1, 5, 6] {
+            assert_eq!(queue.try_transmit(&frame(id, 0)), Ok(None));
+        }
+
+        assert_eq!(queue.try_transmit(&frame(9, 0)), Err(nb::Error::WouldBlock));
+        assert_eq!(queue.try_transmit(&frame(6, 1)), Err(nb::Error::WouldBlock));
+        assert_eq!(queue.try_transmit(&frame(3, 0)), Ok(Some(frame(6, 0))));
+
+        let expected = [(raw(1), 0), (raw(3), 0), (raw(5), 0)];
+        assert_eq!(drain(&mut queue, &mut [(0, 0); 3]), expected);
+    }
+
+    #[test]
+    fn refill_error_keeps_frames() {
+        let mut queue = TxQueue::<_, 1>::new(Mailboxes::<1>::new());
+        queue.try_transmit(&frame(1, 0)).unwrap();
+        queue.try_transmit(&frame(5, 0)).unwrap();
+
+        // The frame is queued even though moving it into the mailbox fails.
+        queue.transmitter.fail = Some(frame(3, 0).id());
+        assert_eq!(queue.try_transmit(&frame(3, 0)), Ok(Some(frame(5, 0))));
+        assert_eq!(queue.len(), 1);
+
+        queue.transmitter.send();
+        assert_eq!(queue.refill(), Err(ErrorKind::Other));
+        queue.transmitter.fail = None;
+        queue.refill().unwrap();
+        assert_eq!(drain(&mut queue, &mut [(0, 0); 1]), [(raw(3), 0)]);
+    }
+
+    #[test]
+    fn abort() {
+        let mut queue = TxQueue::<_, 4>::new(Mailboxes::<1>::new());
+        for (id, data) in [(1, 0), (5, 0), (6, 0), (5, 1)] {
+            queue.try_transmit(&frame(id, data)).unwrap();
+        }
+
+        let id = frame(5, 0).id();
+        assert_eq!(queue.abort(&id), Ok(AbortOutcome::Aborted));
+        assert_eq!(queue.len(), 1);
+        assert_eq!(queue.abort(&id), Ok(AbortOutcome::NotPending));
+        assert_eq!(queue.abort(&frame(1, 0).id()), Ok(AbortOutcome::Aborted));
+
+        queue.refill().unwrap();
+        assert_eq!(drain(&mut queue, &mut [(0, 0); 1]), [(raw(6), 0)]);
+    }
+
+    #[test]
+    fn abort_reports_the_transmitter_outcome() {
+        let mut queue = TxQueue::<_, 4>::new(Mailboxes::<1>::new());
+        for data in 0..3 {
+            queue.try_transmit(&frame(5, data)).unwrap();
+        }
+
+        let id = frame(5, 0).id();
+        queue.transmitter.in_progress = Some(id);
+        assert_eq!(queue.abort(&id), Ok(AbortOutcome::InProgress));
+        assert!(queue.is_empty());
+        assert_eq!(queue.transmitter.send(), Some(frame(5, 0)));
+    }
+
+    #[test]
+    fn abort_error_keeps_queued_frames() {
+        let mut queue = TxQueue::<_, 4>::new(Mailboxes::<1>::new());
+        for id in [1, 5, 5] {
+            queue.try_transmit(&frame(id, 0)).unwrap();
+        }
+
+        let id = frame(5, 0).id();
+        queue.transmitter.fail = Some(id);
+        assert_eq!(queue.abort(&id), Err(ErrorKind::Other));
+        assert_eq!(queue.len(), 2);
+
+        let mut reported = 0;
+        assert_eq!(queue.abort_all(|_, _| reported += 1), Err(ErrorKind::Other));
+        assert_eq!(reported, 0);
+        assert_eq!(queue.len(), 2);
+
+        queue.transmitter.fail = None;
+        let mut reported = [None; 3];
+        let mut index = 0;
+        queue
+            .abort_all(|id, outcome| {
+                reported[index] = Some((id, outcome));
+                index += 1;
+            })
+            .unwrap();
+        let expected = [1, 5, 5].map(|id| Some((frame(id, 0).id(), AbortOutcome::Aborted)));
+        assert_eq!(reported, expected);
+        assert!(queue.is_empty());
+    }
+}
@@ -0,0 +1,349 @@
+//! Sharing a transmitter between tasks
+//!
+//! `Shared` owns a `Transmitter` and hands out up to `N` `SharedTransmitter` handles, one for
+//! every task that needs to transmit. When several tasks wait to transmit, the frame with the
+//! highest priority goes first, so a low priority sender can never block a high priority one.
+//!
+//! Without the `critical-section` feature the handles can only be used by tasks of a single
+//! executor. With it, they can also be used from other executors and interrupt handlers.
+
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll};
+
+use crate::mutex::Mutex;
+use crate::{ArbitrationField, Frame, Interface, Transmitter};
+
+struct State<T: Transmitter, const N: usize> {
+    transmitter: T,
+
+    /// Whether each slot is reserved by a `SharedTransmitter` handle.
+    reserved: [bool; N],
+
+    /// The arbitration fields of the frames waiting in `SharedTransmit` futures, indexed by
+    /// the slot of their handle.
+    waiting: [Option<ArbitrationField>; N],
+
+    /// Frames replaced by the transmitter, to be transmitted again on behalf of their sender.
+    displaced: [Option<T::Frame>; N],
+}
+
+impl<T: Transmitter, const N: usize> State<T, N> {
+    /// Returns true if a frame with higher priority than `field` is waiting or displaced,
+    /// ignoring `slot`.
+    fn outranked(&self, field: ArbitrationField, slot: Option<usize>) -> bool {
+        let waiting = self.waiting.iter().enumerate().any(|(index, waiting)| {
+            Some(index) != slot && matches!(waiting, Some(waiting) if *waiting < field)
+        });
+        waiting
+            || self
+                .displaced
+                .iter()
+                .flatten()
+                .any(|frame| frame.arbitration_field() < field)
+    }
+
+    /// Puts `frame` in the transmit buffer, keeping the frame it replaces.
+    ///
+    /// Returns `WouldBlock` if there is no room left for a replaced frame.
+    fn transmit(&mut self, frame: &T::Frame) -> nb::Result<(), T::Error> {
+        let free = self
+            .displaced
+            .iter()
+            .position(Option::is_none)
+            .ok_or(nb::Error::WouldBlock)?;
+        self.displaced[free] = self.transmitter.try_transmit(frame)?;
+        Ok(())
+    }
+
+    /// Moves displaced frames back into the transmit buffer, highest priority first, until
+    /// it is full or a frame waiting in a `SharedTransmit` future has a higher priority.
+    fn refill(&mut self) -> Result<(), T::Error> {
+        loop {
+            let index = (0..N)
+                .filter(|&index| self.displaced[index].is_some())
+                .min_by_key(|&index| self.displaced[index].as_ref().unwrap().arbitration_field());
+            let frame = match index {
+                Some(index) => self.displaced[index].as_ref().unwrap(),
+                None => return Ok(()),
+            };
+            if self.outranked(frame.arbitration_field(), None) {
+                return Ok(());
+            }
+            match self.transmitter.try_transmit(frame) {
+                // The replaced frame takes the slot of the frame it was replaced by.
+                Ok(replaced) => self.displaced[index.unwrap()] = replaced,
+                Err(nb::Error::WouldBlock) => return Ok(()),
+                Err(nb::Error::Other(e)) => return Err(e),
+            }
+        }
+    }
+}
+
+/// A `Transmitter` shared between tasks through `SharedTransmitter` handles.
+///
+/// Up to `N` handles can exist at the same time, one for each task transmitting concurrently.
+///
+/// Frames replaced in the transmit buffer by higher priority frames are kept and transmitted
+/// again, ahead of lower priority frames. They only move on when a handle is used, or when
+/// `refill` is called. Call `refill` when a mailbox becomes free (e.g. from the transmit
+/// interrupt).
+pub struct Shared<T: Transmitter, const N: usize> {
+    state: Mutex<State<T, N>>,
+}
+
+impl<T: Transmitter, const N: usize> Shared<T, N> {
+    /// Creates a `Shared` owning `transmitter`.
+    pub fn new(transmitter: T) -> Self {
+        Shared {
+            state: Mutex::new(State {
+                transmitter,
+                reserved: [false; N],
+                waiting: [None; N],
+                displaced: core::array::from_fn(|_| None),
+            }),
+        }
+    }
+
+    /// Returns a handle transmitting through the shared transmitter, or `None` if `N`
+    /// handles already exist.
+    pub fn handle(&self) -> Option<SharedTransmitter<'_, T, N>> {
+        self.state.lock(|state| {
+            let slot = state.reserved.iter().position(|reserved| !reserved)?;
+            state.reserved[slot] = true;
+            Some(SharedTransmitter { shared: self, slot })
+        })
+    }
+
+    /// Move displaced frames back into the shared transmitter, highest priority first, until
+    /// it is full.
+    pub fn refill(&self) -> Result<(), T::Error> {
+        self.state.lock(State::refill)
+    }
+
+    /// Returns the shared transmitter, dropping the displaced frames.
+    pub fn into_inner(self) -> T {
+        self.state.into_inner().transmitter
+    }
+}
+
+/// A handle to a `Shared` transmitter.
+///
+/// The handle reserves one of the `N` slots of the `Shared` transmitter until it is dropped,
+/// so its frames can always wait for transmission in priority order.
+pub struct SharedTransmitter<'a, T: Transmitter, const N: usize> {
+    shared: &'a Shared<T, N>,
+    slot: usize,
+}
+
+impl<'a, T: Transmitter, const N: usize> Drop for SharedTransmitter<'a, T, N> {
+    fn drop(&mut self) {
+        self.shared
+            .state
+            .lock(|state| state.reserved[self.slot] = false);
+    }
+}
+
+impl<'a, T: Transmitter, const N: usize> Interface for SharedTransmitter<'a, T, N> {
+    type Id = T::Id;
+    type Frame = T::Frame;
+    type Error = T::Error;
+    type Filter = T::Filter;
+}
+
+impl<'a, T: Transmitter, const N: usize> Transmitter for SharedTransmitter<'a, T, N> {
+    type TransmitterFuture<'b>
+        = SharedTransmit<'b, T, N>
+    where
+        Self: 'b;
+
+    /// Put a `Frame` in the transmit buffer of the shared transmitter.
+    ///
+    /// Returns `WouldBlock` while a frame with higher priority is waiting in another task.
+    /// A replaced lower priority `Frame` is kept by the `Shared` transmitter and transmitted
+    /// again, so this never returns a `Frame`.
+    ///
+    /// Errors occurring while moving displaced frames back into the shared transmitter are
+    /// returned to the caller, and `frame` is then not transmitted.
+    fn try_transmit(
+        &mut self,
+        frame: &Self::Frame,
+    ) -> nb::Result<Option<Self::Frame>, Self::Error> {
+        self.shared.state.lock(|state| {
+            state.refill().map_err(nb::Error::Other)?;
+            if state.outranked(frame.arbitration_field(), None) {
+                return Err(nb::Error::WouldBlock);
+            }
+            state.transmit(frame).map(|()| None)
+        })
+    }
+
+    fn transmit<'b>(&'b mut self, frame: &'b Self::Frame) -> Self::TransmitterFuture<'b> {
+        SharedTransmit {
+            shared: self.shared,
+            frame,
+            slot: self.slot,
+        }
+    }
+}
+
+/// The future returned by `SharedTransmitter::transmit`.
+///
+/// It polls the shared transmitter and wakes itself up immediately when it can not transmit yet,
+/// as `future::PollTransmit` does.
+pub struct SharedTransmit<'a, T: Transmitter, const N: usize> {
+    shared: &'a Shared<T, N>,
+    frame: &'a T::Frame,
+
+    /// The slot of the handle, announcing this frame while it waits.
+    slot: usize,
+}
+
+impl<'a, T: Transmitter, const N: usize> Future for SharedTransmit<'a, T, N> {
+    type Output = Result<Option<T::Frame>, T::Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+        let (frame, slot) = (this.frame, this.slot);
+        let field = frame.arbitration_field();
+
+        let result = this.shared.state.lock(|state| {
+            state.waiting[slot] = Some(field);
+            let result = state.refill().map_err(nb::Error::Other).and_then(|()| {
+                if state.outranked(field, Some(slot)) {
+                    return Err(nb::Error::WouldBlock);
+                }
+                state.transmit(frame)
+            });
+            if !matches!(result, Err(nb::Error::WouldBlock)) {
+                state.waiting[slot] = None;
+            }
+            result
+        });
+
+        match result {
+            Ok(()) => Poll::Ready(Ok(None)),
+            Err(nb::Error::Other(e)) => Poll::Ready(Err(e)),
+            Err(nb::Error::WouldBlock) => {
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+    }
+}
+
+impl<'a, T: Transmitter, const N: usize> Drop for SharedTransmit<'a, T, N> {
+    fn drop(&mut self) {
+        self.shared
+            .state
+            .lock(|state| state.waiting[self.slot] = None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{frame, poll_once, Mailboxes};
+    use crate::ClassicFrame;
+
+    /// Returns a `Shared` transmitter with a single mailbox, which is full and never replaced.
+    fn shared<const N: usize>() -> Shared<Mailboxes<1>, N> {
+        let mut mailboxes = Mailboxes::new();
+        mailboxes.replace = false;
+        mailboxes.mailboxes[0] = Some(frame(1, 0));
+        Shared::new(mailboxes)
+    }
+
+    fn send<const N: usize>(shared: &Shared<Mailboxes<1>, N>) -> Option<ClassicFrame> {
+        shared.state.lock(|state| state.transmitter.send())
+    }
+
+    #[test]
+    fn highest_priority_first() {
+        let shared = shared::<3>();
+        let (mut low, mut high) = (shared.handle().unwrap(), shared.handle().unwrap());
+        let (low_frame, high_frame) = (frame(7, 0), frame(3, 0));
+
+        let mut low = low.transmit(&low_frame);
+        let mut high = high.transmit(&high_frame);
+        assert!(poll_once(Pin::new(&mut low)).is_pending());
+        assert!(poll_once(Pin::new(&mut high)).is_pending());
+
+        // The mailbox is free, but the higher priority frame is waiting.
+        assert_eq!(send(&shared), Some(frame(1, 0)));
+        assert!(poll_once(Pin::new(&mut low)).is_pending());
+        assert_eq!(
+            shared.handle().unwrap().try_transmit(&frame(5, 0)),
+            Err(nb::Error::WouldBlock)
+        );
+        assert_eq!(poll_once(Pin::new(&mut high)), Poll::Ready(Ok(None)));
+
+        assert_eq!(send(&shared), Some(frame(3, 0)));
+        assert_eq!(poll_once(Pin::new(&mut low)), Poll::Ready(Ok(None)));
+        assert_eq!(send(&shared), Some(frame(7, 0)));
+    }
+
+    #[test]
+    fn dropped_future_stops_waiting() {
+        let shared = shared::<2>();
+        let mut handle = shared.handle().unwrap();
+        let high_frame = frame(3, 0);
+
+        let mut high = handle.transmit(&high_frame);
+        assert!(poll_once(Pin::new(&mut high)).is_pending());
+        drop(high);
+
+        assert_eq!(send(&shared), Some(frame(1, 0)));
+        assert_eq!(
+            shared.handle().unwrap().try_transmit(&frame(5, 0)),
+            Ok(None)
+        );
+        assert_eq!(send(&shared), Some(frame(5, 0)));
+    }
+
+    #[test]
+    fn displaced_frames_are_resent() {
+        let shared = Shared::<_, 2>::new(Mailboxes::<1>::new());
+        let (mut first, mut second) = (shared.handle().unwrap(), shared.handle().unwrap());
+        assert_eq!(first.try_transmit(&frame(7, 0)), Ok(None));
+
+        // The displaced frame is kept, and goes before lower priority frames.
+        assert_eq!(second.try_transmit(&frame(3, 0)), Ok(None));
+        assert_eq!(first.try_transmit(&frame(9, 0)), Err(nb::Error::WouldBlock));
+        assert_eq!(send(&shared), Some(frame(3, 0)));
+        shared.refill().unwrap();
+        assert_eq!(send(&shared), Some(frame(7, 0)));
+        assert_eq!(first.try_transmit(&frame(9, 0)), Ok(None));
+        assert_eq!(send(&shared), Some(frame(9, 0)));
+    }
+
+    #[test]
+    fn displaced_frames_are_not_dropped() {
+        let shared = Shared::<_, 1>::new(Mailboxes::<1>::new());
+        let mut handle = shared.handle().unwrap();
+        assert_eq!(handle.try_transmit(&frame(7, 0)), Ok(None));
+        assert_eq!(handle.try_transmit(&frame(3, 0)), Ok(None));
+
+        // There is no room left for another displaced frame.
+        assert_eq!(
+            handle.try_transmit(&frame(1, 0)),
+            Err(nb::Error::WouldBlock)
+        );
+        assert_eq!(send(&shared), Some(frame(3, 0)));
+        assert_eq!(handle.try_transmit(&frame(1, 0)), Ok(None));
+        assert_eq!(send(&shared), Some(frame(1, 0)));
+        shared.refill().unwrap();
+        assert_eq!(send(&shared), Some(frame(7, 0)));
+    }
+
+    #[test]
+    fn handles_reserve_slots() {
+        let shared = shared::<2>();
+        let first = shared.handle().unwrap();
+        let _second = shared.handle().unwrap();
+        assert!(shared.handle().is_none());
+
+        drop(first);
+        assert!(shared.handle().is_some());
+    }
+}
@@ -0,0 +1,232 @@
+//! Helpers shared by the unit tests
+
+extern crate std;
+
+use core::future::Future;
+use core::pin::Pin;
+use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+use std::boxed::Box;
+
+use crate::filter::SoftwareFilter;
+use crate::future::{PollReceive, PollReceiveEvent, PollTransmit};
+use crate::{
+    Abort, AbortOutcome, AnyId, BusEvent, ClassicFrame, ErrorKind, EventReceiver, ExtendedId,
+    Frame, Interface, Receiver, StandardId, Transmitter,
+};
+
+/// Returns the standard identifier `raw`.
+pub(crate) fn standard(raw: u16) -> AnyId {
+    StandardId::new(raw).unwrap().into()
+}
+
+/// Returns the extended identifier `raw`.
+pub(crate) fn extended(raw: u32) -> AnyId {
+    ExtendedId::new(raw).unwrap().into()
+}
+
+/// Returns a data frame with the standard identifier `id`, carrying the single byte `data`.
+pub(crate) fn frame(id: u16, data: u8) -> ClassicFrame {
+    ClassicFrame::new(StandardId::new(id).unwrap(), &[data]).unwrap()
+}
+
+/// Polls `future` once, with a waker that does nothing.
+pub(crate) fn poll_once<F: Future>(future: Pin<&mut F>) -> Poll<F::Output> {
+    fn clone(_: *const ()) -> RawWaker {
+        RawWaker::new(core::ptr::null(), &VTABLE)
+    }
+    fn noop(_: *const ()) {}
+    static VTABLE: RawWakerVTable = RawWakerVTable::new(clone, noop, noop, noop);
+
+    // Safety: the vtable functions do not use the data pointer.
+    let waker = unsafe { Waker::from_raw(clone(core::ptr::null())) };
+    future.poll(&mut Context::from_waker(&waker))
+}
+
+/// Pins `future` on the heap, so it can be polled with `poll_once`.
+pub(crate) fn pinned<F: Future>(future: F) -> Pin<Box<F>> {
+    Box::pin(future)
+}
+
+/// A transmitter with `M` mailboxes.
+///
+/// When all mailboxes are full, a frame replaces the lowest priority one if `replace` is set
+/// and the frame has a higher priority.
+pub(crate) struct Mailboxes<const M: usize> {
+    pub(crate) mailboxes: [Option<ClassicFrame>; M],
+    pub(crate) replace: bool,
+
+    /// The number of calls that would block before the mailboxes are accessible.
+    pub(crate) busy: usize,
+
+    /// Frames with this identifier fail to be transmitted.
+    pub(crate) fail: Option<AnyId>,
+
+    /// Frames with this identifier are being transmitted, and can not be aborted.
+    pub(crate) in_progress: Option<AnyId>,
+}
+
+impl<const M: usize> Mailboxes<M> {
+    pub(crate) fn new() -> Self {
+        Mailboxes {
+            mailboxes: [None; M],
+            replace: true,
+            busy: 0,
+            fail: None,
+            in_progress: None,
+        }
+    }
+
+    /// Sends the highest priority frame on the bus, if any.
+    pub(crate) fn send(&mut self) -> Option<ClassicFrame> {
+        let index = (0..M)
+            .filter(|&index| self.mailboxes[index].is_some())
+            .min_by_key(|&index| self.mailboxes[index].unwrap().arbitration_field())?;
+        self.mailboxes[index].take()
+    }
+}
+
+impl<const M: usize> Interface for Mailboxes<M> {
+    type Id = AnyId;
+    type Frame = ClassicFrame;
+    type Error = ErrorKind;
+    type Filter = SoftwareFilter<AnyId>;
+}
+
+impl<const M: usize> Transmitter for Mailboxes<M> {
+    type TransmitterFuture<'a>
+        = PollTransmit<'a, Self>
+    where
+        Self: 'a;
+
+    fn try_transmit(
+        &mut self,
+        frame: &ClassicFrame,
+    ) -> nb::Result<Option<ClassicFrame>, ErrorKind> {
+        if self.busy > 0 {
+            self.busy -= 1;
+            return Err(nb::Error::WouldBlock);
+        }
+        if Some(frame.id()) == self.fail {
+            return Err(nb::Error::Other(ErrorKind::Other));
+        }
+        if let Some(free) = self.mailboxes.iter_mut().find(|mailbox| mailbox.is_none()) {
+            *free = Some(*frame);
+            return Ok(None);
+        }
+        let lowest = self
+            .mailboxes
+            .iter_mut()
+            .max_by_key(|mailbox| mailbox.unwrap().arbitration_field())
+            .unwrap();
+        if self.replace && frame.priority_cmp(&lowest.unwrap()).is_lt() {
+            Ok(lowest.replace(*frame))
+        } else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
+
+    fn transmit<'a>(&'a mut self, frame: &'a ClassicFrame) -> Self::TransmitterFuture<'a> {
+        PollTransmit::new(self, frame)
+    }
+}
+
+impl<const M: usize> Abort for Mailboxes<M> {
+    fn abort(&mut self, id: &AnyId) -> Result<AbortOutcome, ErrorKind> {
+        if Some(*id) == self.fail {
+            return Err(ErrorKind::Other);
+        }
+        if Some(*id) == self.in_progress {
+            return Ok(AbortOutcome::InProgress);
+        }
+        let mut outcome = AbortOutcome::NotPending;
+        for mailbox in self.mailboxes.iter_mut() {
+            if matches!(mailbox, Some(frame) if frame.id() == *id) {
+                *mailbox = None;
+                outcome = AbortOutcome::Aborted;
+            }
+        }
+        Ok(outcome)
+    }
+
+    fn abort_all<R>(&mut self, mut report: R) -> Result<(), ErrorKind>
+    where
+        R: FnMut(AnyId, AbortOutcome),
+    {
+        if self.fail.is_some() {
+            return Err(ErrorKind::Other);
+        }
+        for frame in self.mailboxes.iter_mut().filter_map(Option::take) {
+            report(frame.id(), AbortOutcome::Aborted);
+        }
+        Ok(())
+    }
+}
+
+/// A receiver returning `frames` in order, then an error.
+pub(crate) struct Frames<const N: usize> {
+    frames: [ClassicFrame; N],
+    next: usize,
+
+    /// The number of calls that would block before the next frame is received.
+    pub(crate) busy: usize,
+}
+
+impl<const N: usize> Frames<N> {
+    pub(crate) fn new(frames: [ClassicFrame; N]) -> Self {
+        Frames {
+            frames,
+            next: 0,
+            busy: 0,
+        }
+    }
+}
+
+impl<const N: usize> Interface for Frames<N> {
+    type Id = AnyId;
+    type Frame = ClassicFrame;
+    type Error = ErrorKind;
+    type Filter = SoftwareFilter<AnyId>;
+}
+
+impl<const N: usize> Receiver for Frames<N> {
+    type ReceiverFuture<'a>
+        = PollReceive<'a, Self>
+    where
+        Self: 'a;
+
+    fn try_receive(&mut self) -> nb::Result<ClassicFrame, ErrorKind> {
+        if self.busy > 0 {
+            self.busy -= 1;
+            return Err(nb::Error::WouldBlock);
+        }
+        let frame = self
+            .frames
+            .get(self.next)
+            .ok_or(nb::Error::Other(ErrorKind::Other))?;
+        self.next += 1;
+        Ok(*frame)
+    }
+
+    fn receive(&mut self) -> Self::ReceiverFuture<'_> {
+        PollReceive::new(self)
+    }
+
+    fn set_filter(&mut self, _: SoftwareFilter<AnyId>) {}
+
+    fn clear_filter(&mut self) {}
+}
+
+impl<const N: usize> EventReceiver for Frames<N> {
+    type EventFuture<'a>
+        = PollReceiveEvent<'a, Self>
+    where
+        Self: 'a;
+
+    fn try_receive_event(&mut self) -> nb::Result<BusEvent<ClassicFrame>, ErrorKind> {
+        self.try_receive().map(BusEvent::Frame)
+    }
+
+    fn receive_event(&mut self) -> Self::EventFuture<'_> {
+        PollReceiveEvent::new(self)
+    }
+}
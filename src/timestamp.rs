@@ -0,0 +1,170 @@
+//! Hardware timestamps
+
+use core::time::Duration;
+
+use crate::{Frame, Timestamped};
+
+/// A capture of the timestamp counter of a Can controller.
+///
+/// The counter runs at a controller specific tick rate, and wraps around at its width.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct Timestamp(u64);
+
+impl Timestamp {
+    /// Creates a `Timestamp` from a raw counter value.
+    #[inline]
+    pub const fn new(ticks: u64) -> Self {
+        Timestamp(ticks)
+    }
+
+    /// Returns the raw counter value.
+    #[inline]
+    pub const fn ticks(&self) -> u64 {
+        self.0
+    }
+
+    /// Returns the number of ticks elapsed since `earlier`, for a counter of `bits` bits which
+    /// wrapped around at most once in between.
+    pub const fn ticks_since(&self, earlier: Timestamp, bits: u32) -> u64 {
+        let mask = if bits >= 64 {
+            u64::MAX
+        } else {
+            (1 << bits) - 1
+        };
+        self.0.wrapping_sub(earlier.0) & mask
+    }
+
+    /// Converts the counter value to a `Duration`, for a counter running at `tick_rate_hz`.
+    ///
+    /// ### Panic
+    /// Panics if `tick_rate_hz` is 0.
+    pub fn to_duration(&self, tick_rate_hz: u32) -> Duration {
+        ticks_to_duration(self.0, tick_rate_hz)
+    }
+}
+
+/// Converts a number of ticks to a `Duration`, for a counter running at `tick_rate_hz`.
+///
+/// ### Panic
+/// Panics if `tick_rate_hz` is 0.
+pub fn ticks_to_duration(ticks: u64, tick_rate_hz: u32) -> Duration {
+    let rate = tick_rate_hz as u64;
+    let nanos = (ticks % rate) * 1_000_000_000 / rate;
+    Duration::from_secs(ticks / rate) + Duration::from_nanos(nanos)
+}
+
+/// A frame along with the time it was received or transmitted at.
+///
+/// Implements `Frame` by delegating to the wrapped frame, so it can be used as the `Frame` type
+/// of an `Interface`. Frames created with `Frame::new` or `Frame::new_remote` have a zero
+/// timestamp.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Stamped<F> {
+    /// The frame.
+    pub frame: F,
+
+    /// The time the frame was received or transmitted at.
+    pub timestamp: Timestamp,
+}
+
+impl<F: Frame> Frame for Stamped<F> {
+    type Id = F::Id;
+
+    fn new(id: impl Into<Self::Id>, data: &[u8]) -> Option<Self> {
+        Some(Stamped {
+            frame: F::new(id, data)?,
+            timestamp: Timestamp::new(0),
+        })
+    }
+
+    fn new_remote(id: impl Into<Self::Id>, dlc: usize) -> Option<Self> {
+        Some(Stamped {
+            frame: F::new_remote(id, dlc)?,
+            timestamp: Timestamp::new(0),
+        })
+    }
+
+    fn is_remote_frame(&self) -> bool {
+        self.frame.is_remote_frame()
+    }
+
+    fn is_data_frame(&self) -> bool {
+        self.frame.is_data_frame()
+    }
+
+    fn id(&self) -> Self::Id {
+        self.frame.id()
+    }
+
+    fn dlc(&self) -> usize {
+        self.frame.dlc()
+    }
+
+    fn data(&self) -> Option<&[u8]> {
+        self.frame.data()
+    }
+}
+
+impl<F> Timestamped for Stamped<F> {
+    fn timestamp(&self) -> Timestamp {
+        self.timestamp
+    }
+}
+
+/// The report of a frame that was transmitted, see `TxEventReceiver`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct TxEvent<I, H> {
+    /// The identifier of the transmitted frame.
+    pub id: I,
+
+    /// The handle of the transmitted frame, if it was put in the transmit buffer with one, e.g.
+    /// by `TrackedTransmitter::try_transmit_tracked`.
+    pub handle: Option<H>,
+
+    /// The time the frame was transmitted at.
+    pub timestamp: Timestamp,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ClassicFrame, StandardId};
+
+    #[test]
+    fn duration() {
+        assert_eq!(Timestamp::new(0).to_duration(1_000), Duration::ZERO);
+        assert_eq!(
+            Timestamp::new(1_500).to_duration(1_000),
+            Duration::from_millis(1_500)
+        );
+        assert_eq!(
+            Timestamp::new(3).to_duration(80_000_000),
+            Duration::from_nanos(37)
+        );
+        assert_eq!(
+            Timestamp::new(u64::MAX).to_duration(u32::MAX),
+            Duration::new(4_294_967_297, 0)
+        );
+    }
+
+    #[test]
+    fn wrapping() {
+        let earlier = Timestamp::new(0xFFF0);
+        assert_eq!(Timestamp::new(0x0010).ticks_since(earlier, 16), 0x20);
+        assert_eq!(Timestamp::new(0xFFF8).ticks_since(earlier, 16), 0x8);
+        assert_eq!(
+            Timestamp::new(5).ticks_since(Timestamp::new(u64::MAX), 64),
+            6
+        );
+    }
+
+    #[test]
+    fn stamped_frame() {
+        let id = StandardId::new(0x123).unwrap();
+        let frame = Stamped::<ClassicFrame>::new(id, &[1, 2]).unwrap();
+        assert_eq!(frame.timestamp(), Timestamp::new(0));
+        assert_eq!(frame.id(), id.into());
+        assert_eq!(frame.data(), Some(&[1, 2][..]));
+        assert!(Stamped::<ClassicFrame>::new_remote(id, 9).is_none());
+    }
+}
@@ -0,0 +1,333 @@
+//! Bit timing calculation
+//!
+//! A Can bit is divided into time quanta (tq), each lasting `prescaler` peripheral clock cycles.
+//! A bit consists of the synchronization segment (always 1 tq), followed by `seg1` (propagation
+//! and phase segment 1) and `seg2` (phase segment 2). The bit is sampled between `seg1` and
+//! `seg2`.
+//!
+//! Sample points are given in permille of the bit time, e.g. `875` for 87.5%.
+
+/// The bit timing parameters of a Can controller.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct BitTiming {
+    /// Peripheral clock cycles per time quantum.
+    pub prescaler: u16,
+
+    /// Time quanta in the propagation and phase 1 segments (TSEG1).
+    pub seg1: u16,
+
+    /// Time quanta in the phase 2 segment (TSEG2).
+    pub seg2: u16,
+
+    /// Synchronization jump width in time quanta (SJW).
+    pub sjw: u16,
+}
+
+impl BitTiming {
+    /// Returns the number of time quanta in a bit.
+    pub const fn tq_per_bit(&self) -> u32 {
+        1 + self.seg1 as u32 + self.seg2 as u32
+    }
+
+    /// Returns the bitrate in bit/s obtained with a peripheral clock of `clock_hz`.
+    pub const fn bitrate(&self, clock_hz: u32) -> u32 {
+        clock_hz / (self.prescaler as u32 * self.tq_per_bit())
+    }
+
+    /// Returns the sample point in permille of the bit time.
+    pub const fn sample_point(&self) -> u16 {
+        ((1 + self.seg1 as u32) * 1000 / self.tq_per_bit()) as u16
+    }
+}
+
+/// The range of bit timing parameters a Can controller supports.
+///
+/// All bounds are inclusive, and given in the same units as the fields of `BitTiming`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct BitTimingLimits {
+    /// Smallest supported prescaler.
+    pub prescaler_min: u16,
+
+    /// Largest supported prescaler.
+    pub prescaler_max: u16,
+
+    /// Smallest supported TSEG1.
+    pub seg1_min: u16,
+
+    /// Largest supported TSEG1.
+    pub seg1_max: u16,
+
+    /// Smallest supported TSEG2.
+    pub seg2_min: u16,
+
+    /// Largest supported TSEG2.
+    pub seg2_max: u16,
+
+    /// Largest supported SJW.
+    pub sjw_max: u16,
+}
+
+/// A `BitTiming` found by `calculate`, along with the bitrate it actually achieves.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Solution {
+    /// The bit timing parameters.
+    pub timing: BitTiming,
+
+    /// The bitrate achieved by `timing`, in bit/s.
+    pub bitrate: u32,
+
+    /// The deviation of `bitrate` from the requested bitrate, in parts per million.
+    pub bitrate_error_ppm: u32,
+}
+
+/// The transmitter delay compensation (TDC) settings of a Can-FD data phase.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Tdc {
+    /// The secondary sample point offset from the measured transmitter delay, in peripheral
+    /// clock cycles.
+    pub offset: u16,
+}
+
+/// A data phase `BitTiming` found by `calculate_data_phase`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct DataPhaseSolution {
+    /// The data phase bit timing.
+    pub solution: Solution,
+
+    /// The transmitter delay compensation to enable, if needed.
+    pub tdc: Option<Tdc>,
+}
+
+/// Finds the best `BitTiming` for `bitrate` (in bit/s) with a peripheral clock of `clock_hz`.
+///
+/// The best timing is the one closest to `bitrate`, then closest to `sample_point` (in permille),
+/// then the one with the most time quanta per bit. The SJW is set as large as possible.
+///
+/// Returns `None` if no timing within `limits` exists.
+pub fn calculate(
+    clock_hz: u32,
+    bitrate: u32,
+    sample_point: u16,
+    limits: &BitTimingLimits,
+) -> Option<Solution> {
+    if bitrate == 0 {
+        return None;
+    }
+
+    let mut best: Option<(Solution, u16)> = None;
+
+    for prescaler in limits.prescaler_min.max(1)..=limits.prescaler_max {
+        // Round to the nearest number of time quanta per bit.
+        let cycles_per_tq = bitrate as u64 * prescaler as u64;
+        let tq = ((clock_hz as u64 + cycles_per_tq / 2) / cycles_per_tq) as u32;
+
+        let timing = match split(prescaler, tq, sample_point, limits) {
+            Some(timing) => timing,
+            None => continue,
+        };
+
+        let actual = clock_hz as u64 / (prescaler as u64 * tq as u64);
+        let deviation = (clock_hz as u64).abs_diff(cycles_per_tq * tq as u64);
+        let bitrate_error_ppm = (deviation * 1_000_000 / (cycles_per_tq * tq as u64)) as u32;
+        let sample_point_error = timing.sample_point().abs_diff(sample_point);
+
+        // Prescalers are tried in increasing order, so the first of two otherwise equal
+        // solutions has the most time quanta per bit.
+        let better = match &best {
+            Some((best, best_sp_error)) => {
+                (bitrate_error_ppm, sample_point_error) < (best.bitrate_error_ppm, *best_sp_error)
+            }
+            None => true,
+        };
+        if better {
+            let solution = Solution {
+                timing,
+                bitrate: actual as u32,
+                bitrate_error_ppm,
+            };
+            best = Some((solution, sample_point_error));
+        }
+    }
+
+    best.map(|(solution, _)| solution)
+}
+
+/// Finds the best Can-FD data phase `BitTiming`, see `calculate`.
+///
+/// Transmitter delay compensation is enabled when the data phase prescaler is 1 or 2, as the
+/// transmitter delay may then exceed the sample point. The secondary sample point is placed at
+/// the sample point, but at most `tdc_offset_max` peripheral clock cycles after the measured
+/// transmitter delay.
+pub fn calculate_data_phase(
+    clock_hz: u32,
+    bitrate: u32,
+    sample_point: u16,
+    limits: &BitTimingLimits,
+    tdc_offset_max: u16,
+) -> Option<DataPhaseSolution> {
+    let solution = calculate(clock_hz, bitrate, sample_point, limits)?;
+    let timing = &solution.timing;
+
+    let tdc = if timing.prescaler <= 2 {
+        let offset = timing.prescaler as u32 * (1 + timing.seg1 as u32);
+        Some(Tdc {
+            offset: offset.min(tdc_offset_max as u32) as u16,
+        })
+    } else {
+        None
+    };
+
+    Some(DataPhaseSolution { solution, tdc })
+}
+
+/// Splits `tq` time quanta into segments placing the sample point as close to `sample_point`
+/// as `limits` allow.
+fn split(
+    prescaler: u16,
+    tq: u32,
+    sample_point: u16,
+    limits: &BitTimingLimits,
+) -> Option<BitTiming> {
+    let min = 1 + limits.seg1_min as u32 + limits.seg2_min as u32;
+    let max = 1 + limits.seg1_max as u32 + limits.seg2_max as u32;
+    if tq < min || tq > max {
+        return None;
+    }
+
+    // Time quanta up to and including the sample point, rounded to the nearest.
+    let sampled = (tq * sample_point as u32 + 500) / 1000;
+    let seg2 = tq
+        .saturating_sub(sampled)
+        .clamp(limits.seg2_min as u32, limits.seg2_max as u32);
+    let seg1 = (tq - 1)
+        .saturating_sub(seg2)
+        .clamp(limits.seg1_min as u32, limits.seg1_max as u32);
+    let seg2 = tq - 1 - seg1;
+    if seg2 < limits.seg2_min as u32 || seg2 > limits.seg2_max as u32 {
+        return None;
+    }
+
+    let sjw = seg2.min(seg1).min(limits.sjw_max as u32);
+
+    Some(BitTiming {
+        prescaler,
+        seg1: seg1 as u16,
+        seg2: seg2 as u16,
+        sjw: sjw as u16,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Nominal bit timing limits of a bxCAN peripheral.
+    const BXCAN: BitTimingLimits = BitTimingLimits {
+        prescaler_min: 1,
+        prescaler_max: 1024,
+        seg1_min: 1,
+        seg1_max: 16,
+        seg2_min: 1,
+        seg2_max: 8,
+        sjw_max: 4,
+    };
+
+    /// Data phase bit timing limits of an FDCAN peripheral.
+    const FDCAN_DATA: BitTimingLimits = BitTimingLimits {
+        prescaler_min: 1,
+        prescaler_max: 32,
+        seg1_min: 1,
+        seg1_max: 32,
+        seg2_min: 1,
+        seg2_max: 16,
+        sjw_max: 16,
+    };
+
+    #[test]
+    fn exact_500k_at_36mhz() {
+        let solution = calculate(36_000_000, 500_000, 875, &BXCAN).unwrap();
+        assert_eq!(
+            solution,
+            Solution {
+                timing: BitTiming {
+                    prescaler: 9,
+                    seg1: 6,
+                    seg2: 1,
+                    sjw: 1,
+                },
+                bitrate: 500_000,
+                bitrate_error_ppm: 0,
+            }
+        );
+        assert_eq!(solution.timing.sample_point(), 875);
+    }
+
+    #[test]
+    fn exact_125k_at_8mhz() {
+        let solution = calculate(8_000_000, 125_000, 875, &BXCAN).unwrap();
+        assert_eq!(
+            solution.timing,
+            BitTiming {
+                prescaler: 4,
+                seg1: 13,
+                seg2: 2,
+                sjw: 2,
+            }
+        );
+        assert_eq!(solution.timing.tq_per_bit(), 16);
+        assert_eq!(solution.timing.bitrate(8_000_000), 125_000);
+        assert_eq!(solution.bitrate_error_ppm, 0);
+    }
+
+    #[test]
+    fn inexact_bitrate_reports_error() {
+        // 8 MHz / 300 kbit/s is 26.67 cycles per bit, too many for a single tq per cycle.
+        let solution = calculate(8_000_000, 300_000, 875, &BXCAN).unwrap();
+        assert_eq!(solution.timing.prescaler, 3);
+        assert_eq!(solution.timing.tq_per_bit(), 9);
+        assert_eq!(solution.timing.sample_point(), 888);
+        assert_eq!(solution.bitrate, 296_296);
+        assert_eq!(solution.bitrate_error_ppm, 12_345);
+    }
+
+    #[test]
+    fn unreachable_bitrate() {
+        assert_eq!(calculate(8_000_000, 0, 875, &BXCAN), None);
+        // At most 8 MHz / 3 tq = 2.67 Mbit/s.
+        assert_eq!(calculate(8_000_000, 5_000_000, 875, &BXCAN), None);
+        // At least 8 MHz / (1024 * 25 tq) = 312.5 bit/s.
+        assert_eq!(calculate(8_000_000, 100, 875, &BXCAN), None);
+    }
+
+    #[test]
+    fn data_phase_5m_at_80mhz_with_tdc() {
+        let data = calculate_data_phase(80_000_000, 5_000_000, 750, &FDCAN_DATA, 127).unwrap();
+        let timing = data.solution.timing;
+        assert_eq!(
+            timing,
+            BitTiming {
+                prescaler: 1,
+                seg1: 11,
+                seg2: 4,
+                sjw: 4,
+            }
+        );
+        assert_eq!(data.solution.bitrate_error_ppm, 0);
+        assert_eq!(timing.sample_point(), 750);
+        // The secondary sample point is at the sample point, 12 clock cycles into the bit.
+        assert_eq!(data.tdc, Some(Tdc { offset: 12 }));
+    }
+
+    #[test]
+    fn data_phase_tdc_offset_is_limited() {
+        let data = calculate_data_phase(80_000_000, 5_000_000, 750, &FDCAN_DATA, 10).unwrap();
+        assert_eq!(data.tdc, Some(Tdc { offset: 10 }));
+    }
+
+    #[test]
+    fn data_phase_without_tdc() {
+        let data = calculate_data_phase(80_000_000, 500_000, 800, &FDCAN_DATA, 127).unwrap();
+        assert_eq!(data.solution.timing.prescaler, 4);
+        assert_eq!(data.tdc, None);
+    }
+}
@@ -0,0 +1,41 @@
+//! Transmission outcomes
+
+/// The outcome of aborting a pending frame, see `Abort`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum AbortOutcome {
+    /// The frame was removed before it was transmitted.
+    Aborted,
+
+    /// The frame had already been transmitted successfully.
+    AlreadySent,
+
+    /// The frame is being transmitted, and can not be aborted anymore.
+    InProgress,
+
+    /// No such frame was pending.
+    NotPending,
+}
+
+/// The final state of a frame tracked by a `TrackedTransmitter` or sent by `OneShot`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum TransmitOutcome {
+    /// The frame was transmitted and acknowledged.
+    Sent,
+
+    /// The frame lost arbitration, and was not retransmitted.
+    ArbitrationLost,
+
+    /// The frame was removed before it was transmitted, because it was replaced by a higher
+    /// priority frame or aborted.
+    Removed,
+}
+
+/// A frame put in the transmit buffer by `TrackedTransmitter::try_transmit_tracked`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct Tracked<H, F> {
+    /// The handle identifying the frame.
+    pub handle: H,
+
+    /// The lower priority frame replaced by it, if any.
+    pub replaced: Option<F>,
+}